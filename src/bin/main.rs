@@ -1,36 +1,218 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use celestus::{
-    cache::{settings::HashMapValueTypes, Cache},
-    providers::secrets::{SETTING_SECRETS_PROVIDERS, SETTING_USE_SECRETS_PROVIDER},
-    utils::environment::init_environment,
+    cache::{
+        settings::{
+            consts::APP_SETTINGS, dump::SettingsDump, registry::render_markdown,
+            reload::SETTING_SETTINGS_RELOAD_INTERVAL, snapshot::SettingsReader,
+            sources::load_static_layers,
+        },
+        Cache,
+    },
+    database::{Database, SETTINGS_DATABASE},
+    providers::secrets::SETTING_USE_SECRETS_PROVIDER,
+    utils::{environment::init_environment, errors::ConfigReport},
 };
-use log::info;
+use log::{error, info};
+use std::env;
+use std::fs;
+use std::process::ExitCode;
+use std::time::Duration;
+use tokio::signal;
+use tokio::time::{interval, MissedTickBehavior};
 
-// #[tokio::main(flavor = "multi_thread", worker_threads = 1)]
-// #[tokio::main(flavor = "current_thread")]
-fn main() -> Result<()> {
-    env_logger::init();
+const ARG_PRINT_SETTINGS: &str = "--print-settings";
+const ARG_SETTINGS_DOCS: &str = "--settings-docs";
+const ARG_DIFF_SETTINGS: &str = "--diff-settings";
+const ARG_JSON: &str = "--json";
+const ARG_MIGRATIONS: &str = "--migrations";
 
-    init_environment();
-    let mut cache = Cache::new();
+const MIGRATIONS_LIST: &str = "list";
+const MIGRATIONS_RUN: &str = "run";
+const MIGRATIONS_REVERT: &str = "revert";
+
+// How often the runtime loop picks up changes queued by the background workers
+const RUNTIME_TICK: Duration = Duration::from_secs(1);
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() -> Result<ExitCode> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let has_arg = |name: &str| args.iter().any(|arg| arg == name);
+
+    if has_arg(ARG_SETTINGS_DOCS) {
+        print!("{}", render_markdown(APP_SETTINGS));
+        return Ok(ExitCode::SUCCESS);
+    }
+    if let Some(position) = args.iter().position(|arg| arg == ARG_DIFF_SETTINGS) {
+        return diff_settings(&args[position + 1..]);
+    }
+
+    let mut report = ConfigReport::default();
+    let environment = report.take(init_environment());
+
+    let cache = report.take_all(Cache::load().await);
+
+    if has_arg(ARG_PRINT_SETTINGS) {
+        let dump = match &cache {
+            Some(cache) => cache.settings.dump(),
+            None => SettingsDump::from_layers(
+                APP_SETTINGS,
+                &load_static_layers(&mut ConfigReport::default()),
+            ),
+        };
+        match has_arg(ARG_JSON) {
+            true => println!("{}", dump.to_json()?),
+            false => print!("{}", dump.to_text()),
+        }
+
+        if report.is_empty() {
+            return Ok(ExitCode::SUCCESS);
+        }
+        eprint!("{}", report);
+        return Ok(ExitCode::FAILURE);
+    }
+
+    // Printed directly, the logger may be filtered or not even initialized
+    let (Some(environment), Some(mut cache)) = (environment, cache) else {
+        eprint!("{}", report);
+        return Ok(ExitCode::FAILURE);
+    };
 
     info!(
         "{:?}",
         cache.settings.get_bool(SETTING_USE_SECRETS_PROVIDER)
     );
 
-    let providers = cache
-        .settings
-        .get_hashmap(SETTING_SECRETS_PROVIDERS)
-        .unwrap();
-    match providers {
-        HashMapValueTypes::SecretsProviders(sp) => {
-            info!(
-                "{:#?}",
-                sp.get_providers().get(&"vault".into()).unwrap().get_name()
+    let secrets_providers = cache.settings.get_secrets_providers();
+    if let Some(sp) = secrets_providers {
+        for provider in sp.get_providers().values() {
+            info!("{:#?}", provider.get_name());
+        }
+    }
+
+    let mut database = Database::new(environment, cache.settings.scope(SETTINGS_DATABASE));
+    let exit_code = match args.iter().position(|arg| arg == ARG_MIGRATIONS) {
+        Some(position) => {
+            let command = args.get(position + 1).map(String::as_str);
+            migrate(&mut database, &cache, command)
+        }
+        None => serve(&mut database, &mut cache).await,
+    };
+
+    // Dynamic database users should not outlive the process, whichever way it exits
+    if let Some(providers) = cache.settings.get_secrets_providers() {
+        providers.revoke_leases().await;
+    }
+
+    exit_code
+}
+
+fn migrate(database: &mut Database, cache: &Cache, command: Option<&str>) -> Result<ExitCode> {
+    database.connect(cache.settings.get_secrets_providers())?;
+
+    run_migrations_command(database, command)
+}
+
+async fn serve(database: &mut Database, cache: &mut Cache) -> Result<ExitCode> {
+    database.connect_and_init(cache.settings.get_secrets_providers())?;
+
+    match cache.settings.refresh_from_database(database) {
+        Ok(_) => {
+            run(database, cache).await;
+            Ok(ExitCode::SUCCESS)
+        }
+        Err(report) => {
+            error!("{}", report);
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
+
+async fn run(database: &mut Database, cache: &mut Cache) {
+    let mut ticks = interval(RUNTIME_TICK);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut reloader = cache.settings.start_reloader();
+
+    info!("Celestus is running, press Ctrl+C to stop");
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => {}
+            _ = signal::ctrl_c() => break,
+        }
+
+        // The reloader and the watcher only queue their triggers, they are acted on here
+        match cache
+            .settings
+            .reload_pending(&reloader, Some(database))
+            .await
+        {
+            Ok(changes)
+                if changes
+                    .iter()
+                    .any(|change| change.definition.key == SETTING_SETTINGS_RELOAD_INTERVAL) =>
+            {
+                reloader = cache.settings.start_reloader();
+            }
+            Ok(_) => (),
+            Err(report) => error!("Settings were not reloaded: {}", report),
+        }
+
+        if let Err(err) = database.refresh_credentials(cache.settings.get_secrets_providers()) {
+            error!("Database keeps the old connection pool: {}", err);
+        }
+    }
+    info!("Shutting down");
+}
+
+fn diff_settings(paths: &[String]) -> Result<ExitCode> {
+    let [left, right] = paths else {
+        eprintln!("Usage: {} <left.json> <right.json>", ARG_DIFF_SETTINGS);
+        return Ok(ExitCode::FAILURE);
+    };
+
+    let load = |path: &String| -> Result<SettingsDump> {
+        let json = fs::read_to_string(path).with_context(|| format!("cannot read {}", path))?;
+        SettingsDump::from_json(&json).with_context(|| format!("{} is not a settings dump", path))
+    };
+    let differences = load(left)?.diff(&load(right)?);
+
+    if differences.is_empty() {
+        println!("No differences between {} and {}", left, right);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    println!("--- {}\n+++ {}", left, right);
+    for difference in differences.iter() {
+        println!("{}", difference);
+    }
+
+    Ok(ExitCode::FAILURE)
+}
+
+fn run_migrations_command(database: &Database, command: Option<&str>) -> Result<ExitCode> {
+    match command {
+        Some(MIGRATIONS_LIST) => {
+            for migration in database.list_migrations()? {
+                println!("{}", migration);
+            }
+        }
+        Some(MIGRATIONS_RUN) => {
+            let applied = database.run_migrations()?;
+            for name in applied.iter() {
+                println!("Applied {}", name);
+            }
+            if applied.is_empty() {
+                println!("No pending migrations");
+            }
+        }
+        Some(MIGRATIONS_REVERT) => println!("Reverted {}", database.revert_migration()?),
+        _ => {
+            eprintln!(
+                "Usage: {} <{}|{}|{}>",
+                ARG_MIGRATIONS, MIGRATIONS_LIST, MIGRATIONS_RUN, MIGRATIONS_REVERT
             );
+            return Ok(ExitCode::FAILURE);
         }
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
@@ -1,20 +1,21 @@
 pub mod settings;
 
+use crate::utils::errors::ConfigReport;
+
 use self::settings::SettingsCache;
 
-pub struct Cache<'a> {
-    pub settings: SettingsCache<'a>,
+pub struct Cache {
+    pub settings: SettingsCache,
 }
 
-impl Default for Cache<'_> {
-    fn default() -> Self {
-        Self::new()
+impl Cache {
+    pub fn new() -> Result<Self, ConfigReport> {
+        let settings: SettingsCache = SettingsCache::new()?;
+        Ok(Self { settings })
     }
-}
 
-impl Cache<'_> {
-    pub fn new() -> Self {
-        let settings: SettingsCache = SettingsCache::new();
-        Self { settings }
+    pub async fn load() -> Result<Self, ConfigReport> {
+        let settings: SettingsCache = SettingsCache::load().await?;
+        Ok(Self { settings })
     }
 }
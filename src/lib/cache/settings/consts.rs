@@ -1,25 +1,130 @@
+use std::time::Duration;
+
 use crate::{
-    providers::secrets::{ENV_USE_SECRETS_PROVIDER, SETTING_USE_SECRETS_PROVIDER},
-    utils::environment::{ENV_HOST_ENVIRONMENT, SETTING_HOST_MODE},
+    database::{
+        migrate::MIGRATIONS_MODES, ENV_DATABASE_MIGRATIONS_MODE,
+        ENV_DATABASE_POOL_CONNECTION_TIMEOUT, ENV_DATABASE_POOL_MAX_SIZE,
+        SETTING_DATABASE_MIGRATIONS_MODE, SETTING_DATABASE_POOL_CONNECTION_TIMEOUT,
+        SETTING_DATABASE_POOL_MAX_SIZE,
+    },
+    providers::secrets::{
+        cache::ENV_SECRETS_CACHE_TTL, watch::ENV_SECRETS_WATCH_INTERVAL, ENV_SECRETS_PROVIDERS,
+        ENV_USE_SECRETS_PROVIDER, SETTING_SECRETS_PROVIDERS, SETTING_USE_SECRETS_PROVIDER,
+    },
+    settings,
+    utils::environment::{ENVIRONMENT_NAMES, ENV_HOST_ENVIRONMENT, SETTING_HOST_ENVIRONMENT},
 };
 
-use super::SettingsTypes;
-
-pub type AppSettings = &'static [&'static [&'static SettingsTypes<'static>]];
-
-pub const APP_SETTINGS: AppSettings = &[BOOL_SETTINGS, INT32_SETTINGS];
-
-pub const BOOL_SETTINGS: &[&SettingsTypes] = &[&SettingsTypes::Bool(
-    SETTING_USE_SECRETS_PROVIDER,
-    ENV_USE_SECRETS_PROVIDER,
-    Some(false),
-)];
+use super::registry::{SettingKind, Validation};
+use super::reload::{ENV_SETTINGS_RELOAD_INTERVAL, SETTING_SETTINGS_RELOAD_INTERVAL};
+use super::sources::{ENV_SETTINGS_SECRETS_PATH, SETTING_SETTINGS_SECRETS_PATH};
 
-pub const INT32_SETTINGS: &[&SettingsTypes] =
-    &[&SettingsTypes::Int32("some_int", "some_int", Some(123))];
+pub const SETTING_SECRETS_CACHE_TTL: &str = "secrets.cache.ttl";
+pub const SETTING_SECRETS_WATCH_INTERVAL: &str = "secrets.watch.interval";
 
-pub const STRING_SETTINGS: &[&SettingsTypes] = &[&SettingsTypes::String(
-    SETTING_HOST_MODE,
-    ENV_HOST_ENVIRONMENT,
-    None,
-)];
+settings! {
+    APP_SETTINGS {
+        HOST_ENVIRONMENT {
+            key: SETTING_HOST_ENVIRONMENT,
+            env: ENV_HOST_ENVIRONMENT,
+            kind: SettingKind::String,
+            default: Some("production"),
+            description: "Environment celestus runs as, chooses seeds, secrets paths, log levels and security checks",
+            validation: Validation::OneOf(ENVIRONMENT_NAMES),
+            secret: false,
+        }
+        USE_SECRETS_PROVIDER {
+            key: SETTING_USE_SECRETS_PROVIDER,
+            env: ENV_USE_SECRETS_PROVIDER,
+            kind: SettingKind::Bool,
+            default: Some("false"),
+            description: "Load credentials from the Secrets Providers instead of the environment",
+            validation: Validation::None,
+            secret: false,
+        }
+        SECRETS_PROVIDERS {
+            key: SETTING_SECRETS_PROVIDERS,
+            env: ENV_SECRETS_PROVIDERS,
+            kind: SettingKind::List,
+            default: Some(""),
+            description: "Comma separated names of the Secrets Providers, each configured by <NAME>_KIND",
+            validation: Validation::Pattern(r"^[A-Za-z0-9_]+$"),
+            secret: false,
+        }
+        SECRETS_CACHE_TTL {
+            key: SETTING_SECRETS_CACHE_TTL,
+            env: ENV_SECRETS_CACHE_TTL,
+            kind: SettingKind::Duration,
+            default: Some("300s"),
+            description: "How long an on-demand secret stays cached, 0 disables caching",
+            validation: Validation::DurationRange {
+                min: Duration::ZERO,
+                max: Duration::from_secs(86400),
+            },
+            secret: false,
+        }
+        SECRETS_WATCH_INTERVAL {
+            key: SETTING_SECRETS_WATCH_INTERVAL,
+            env: ENV_SECRETS_WATCH_INTERVAL,
+            kind: SettingKind::Duration,
+            default: Some("60s"),
+            description: "Time between checks of watched secrets for new versions",
+            validation: Validation::DurationRange {
+                min: Duration::from_secs(1),
+                max: Duration::from_secs(86400),
+            },
+            secret: false,
+        }
+        SETTINGS_SECRETS_PATH {
+            key: SETTING_SETTINGS_SECRETS_PATH,
+            env: ENV_SETTINGS_SECRETS_PATH,
+            kind: SettingKind::String,
+            default: Some("settings"),
+            description: "Secrets path holding settings that override the config file and environment",
+            validation: Validation::None,
+            secret: false,
+        }
+        SETTINGS_RELOAD_INTERVAL {
+            key: SETTING_SETTINGS_RELOAD_INTERVAL,
+            env: ENV_SETTINGS_RELOAD_INTERVAL,
+            kind: SettingKind::Duration,
+            default: Some("60s"),
+            description: "Time between polls of system_configs for changed settings, 0 disables polling",
+            validation: Validation::DurationRange {
+                min: Duration::ZERO,
+                max: Duration::from_secs(86400),
+            },
+            secret: false,
+        }
+        DATABASE_POOL_MAX_SIZE {
+            key: SETTING_DATABASE_POOL_MAX_SIZE,
+            env: ENV_DATABASE_POOL_MAX_SIZE,
+            kind: SettingKind::Int32,
+            default: Some("10"),
+            description: "Maximum number of connections kept in the database pool",
+            validation: Validation::Range { min: 1, max: 1000 },
+            secret: false,
+        }
+        DATABASE_POOL_CONNECTION_TIMEOUT {
+            key: SETTING_DATABASE_POOL_CONNECTION_TIMEOUT,
+            env: ENV_DATABASE_POOL_CONNECTION_TIMEOUT,
+            kind: SettingKind::Duration,
+            default: Some("30s"),
+            description: "How long to wait for a free database connection before failing",
+            validation: Validation::DurationRange {
+                min: Duration::from_secs(1),
+                max: Duration::from_secs(600),
+            },
+            secret: false,
+        }
+        DATABASE_MIGRATIONS_MODE {
+            key: SETTING_DATABASE_MIGRATIONS_MODE,
+            env: ENV_DATABASE_MIGRATIONS_MODE,
+            kind: SettingKind::Enum(MIGRATIONS_MODES),
+            default: Some("auto"),
+            description: "Whether startup applies pending migrations (auto), fails on them (check) or ignores them (off)",
+            validation: Validation::None,
+            secret: false,
+        }
+    }
+}
@@ -1,92 +1,129 @@
 pub mod consts;
+pub mod dump;
+pub mod registry;
+pub mod reload;
+pub mod snapshot;
+pub mod sources;
+pub mod values;
+pub mod view;
 
-use core::result::Result::Ok;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use crate::{
+    database::{connection::DatabaseConnectable, models::system_config::SystemConfig},
     providers::secrets::{
-        SecretsProvider, SecretsProviders, SETTING_SECRETS_PROVIDERS, SETTING_USE_SECRETS_PROVIDER,
+        SecretsOptions, SecretsProvider, SecretsProviders, SETTING_SECRETS_PROVIDERS,
+        SETTING_USE_SECRETS_PROVIDER,
+    },
+    utils::{
+        environment::{get_environment, Environment, SETTING_HOST_ENVIRONMENT},
+        errors::{ConfigError, ConfigReport},
+        runtime::block_on,
     },
-    utils::environment::{get_env_var, get_host_mode, SETTING_HOST_MODE},
 };
 use anyhow::Result;
-use log::{error, info};
-use lru::LruCache;
-
-use self::consts::APP_SETTINGS;
-
-pub type LruSettingsCache<'a, Value> = LruCache<&'a str, Value>;
+use log::{debug, info};
+
+use self::consts::{APP_SETTINGS, SETTING_SECRETS_CACHE_TTL, SETTING_SECRETS_WATCH_INTERVAL};
+use self::dump::SettingsDump;
+use self::registry::{SettingDefinition, SettingValue};
+use self::reload::{
+    ReloadTrigger, SettingsCallback, SettingsChange, SettingsReloader,
+    SETTING_SETTINGS_RELOAD_INTERVAL,
+};
+use self::snapshot::{SettingsHandle, SettingsReader, SettingsSnapshot};
+use self::sources::{
+    find_config_file, load_static_layers, values_from_map, SettingSource, SettingsLayer,
+    SettingsLayers, ValuesLayer, SETTING_SETTINGS_SECRETS_PATH,
+};
+use self::view::SettingsView;
 
-pub struct SettingsCache<'a> {
-    bools: LruSettingsCache<'a, bool>,
-    ints: LruSettingsCache<'a, i32>,
-    strings: LruSettingsCache<'a, String>,
-    hashmaps: HashMap<&'a str, HashMapValueTypes>,
+pub struct SettingsCache {
+    settings: SettingsHandle,
+    hashmaps: HashMap<&'static str, HashMapValueTypes>,
+    layers: SettingsLayers,
+    callbacks: HashMap<&'static str, Vec<SettingsCallback>>,
 }
 
+type ResolvedSettings = Vec<(&'static SettingDefinition, SettingValue, SettingSource)>;
+
 pub enum HashMapValueTypes {
     SecretsProviders(SecretsProviders),
 }
 
-pub enum SettingsTypes<'a> {
-    Bool(&'a str, &'a str, Option<bool>),
-    Int32(&'a str, &'a str, Option<i32>),
-    String(&'a str, &'a str, Option<String>),
-    Hashmap(&'a str, HashMapValueTypes),
-}
-
-impl<'a> Default for SettingsCache<'a> {
-    fn default() -> Self {
-        Self::new()
+impl SettingsReader for SettingsCache {
+    fn read<Output>(
+        &self,
+        key: &str,
+        reader: impl FnOnce(&SettingsSnapshot, &str) -> Output,
+    ) -> Output {
+        self.settings.read(key, reader)
     }
 }
 
-impl<'a> SettingsCache<'a> {
-    pub fn new() -> Self {
-        let mut lru_bools: LruSettingsCache<bool> = LruCache::unbounded();
-
-        let mut lru_ints: LruSettingsCache<i32> = LruCache::unbounded();
+impl SettingsCache {
+    pub fn new() -> Result<Self, ConfigReport> {
+        block_on(Self::load())
+    }
 
-        let mut lru_strings: LruSettingsCache<String> = LruCache::unbounded();
-        lru_strings.push(SETTING_HOST_MODE, get_host_mode().to_string());
+    pub async fn load() -> Result<Self, ConfigReport> {
+        let mut report = ConfigReport::default();
 
         let hashmaps = HashMap::<&str, HashMapValueTypes>::new();
 
-        match load_env_var_settings(&mut lru_bools, &mut lru_ints, &mut lru_strings) {
-            Ok(_) => (),
-            Err(err) => error!("{}", err),
-        }
+        let layers = load_static_layers(&mut report);
 
         let mut created = Self {
-            bools: lru_bools,
-            ints: lru_ints,
-            strings: lru_strings,
+            settings: SettingsHandle::new(SettingsSnapshot::default()),
             hashmaps,
+            layers,
+            callbacks: HashMap::new(),
         };
 
-        match created.load_structured_settings() {
-            Ok(_) => (),
-            Err(err) => error!("{}", err),
-        }
+        // The Secrets Providers are configured by the static layers, so resolve once without
+        // them and again once their settings can take precedence.
+        created.apply(resolve_layers(
+            &created.layers,
+            &mut ConfigReport::default(),
+        ));
 
-        info!("All settings have bee loaded successfully!");
+        if let Err(err) = created.load_structured_settings().await {
+            report.extend(err);
+        }
 
+        let secrets_layer = created.load_secrets_layer().await;
+        created.layers.push(Arc::new(secrets_layer));
         created
+            .layers
+            .push(Arc::new(ValuesLayer::empty(SettingSource::Database)));
+
+        created.apply(resolve_layers(&created.layers, &mut report));
+
+        if report.is_empty() {
+            info!("All settings have bee loaded successfully!");
+        }
+
+        report.into_result(created)
     }
 
-    pub fn get_bool(&mut self, key: &str) -> Option<&bool> {
-        self.bools.get(key)
+    pub fn handle(&self) -> SettingsHandle {
+        self.settings.clone()
     }
 
-    pub fn get_int(&mut self, key: &str) -> Option<&i32> {
-        self.ints.get(key)
+    pub fn snapshot(&self) -> Arc<SettingsSnapshot> {
+        self.settings.snapshot()
     }
 
-    pub fn get_string(&mut self, key: &str) -> Option<&str> {
-        self.strings.get(key).map(|x| x.as_str())
+    pub fn scope(&self, prefix: &str) -> SettingsView {
+        self.settings.scope(prefix)
     }
 
-    pub fn get_hashmap(&self, key: &'a str) -> Option<&HashMapValueTypes> {
+    pub fn dump(&self) -> SettingsDump {
+        SettingsDump::from_snapshot(APP_SETTINGS, &self.snapshot())
+    }
+
+    pub fn get_hashmap(&self, key: &str) -> Option<&HashMapValueTypes> {
         self.hashmaps.get(key)
     }
 
@@ -101,41 +138,202 @@ impl<'a> SettingsCache<'a> {
         }
     }
 
-    pub fn get_secrets_provider(&self, key: &'a str) -> Option<&SecretsProvider> {
-        match self.get_hashmap(SETTING_SECRETS_PROVIDERS).unwrap() {
-            HashMapValueTypes::SecretsProviders(sp) => sp.get_providers().get(&key.into()),
-            // _ => None,
+    pub fn get_secrets_providers(&self) -> Option<&SecretsProviders> {
+        match self.get_hashmap(SETTING_SECRETS_PROVIDERS)? {
+            HashMapValueTypes::SecretsProviders(sp) => Some(sp),
+        }
+    }
+
+    pub fn get_secrets_provider(&self, key: &str) -> Option<&SecretsProvider> {
+        self.get_secrets_providers()?
+            .get_providers()
+            .get(&key.into())
+    }
+
+    pub fn get_secrets_options(&self) -> Option<SecretsOptions> {
+        Some(SecretsOptions {
+            providers: self.get_list(SETTING_SECRETS_PROVIDERS)?,
+            cache_ttl: self.get_duration(SETTING_SECRETS_CACHE_TTL)?,
+            watch_interval: self.get_duration(SETTING_SECRETS_WATCH_INTERVAL)?,
+        })
+    }
+
+    pub fn on_change(
+        &mut self,
+        key: &'static str,
+        callback: impl Fn(&SettingsChange) + Send + Sync + 'static,
+    ) {
+        self.callbacks
+            .entry(key)
+            .or_default()
+            .push(Box::new(callback));
+    }
+
+    pub fn start_reloader(&self) -> SettingsReloader {
+        let poll_interval = self
+            .get_duration(SETTING_SETTINGS_RELOAD_INTERVAL)
+            .filter(|interval| !interval.is_zero());
+
+        SettingsReloader::start(poll_interval, find_config_file())
+    }
+
+    pub async fn reload_pending(
+        &mut self,
+        reloader: &SettingsReloader,
+        database: Option<&dyn DatabaseConnectable>,
+    ) -> Result<Vec<SettingsChange>, ConfigReport> {
+        let triggers = reloader.take_pending();
+        if triggers.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let only_polls = triggers
+            .iter()
+            .all(|trigger| *trigger == ReloadTrigger::DatabasePoll);
+        match (only_polls, database) {
+            (true, Some(database)) => self.refresh_from_database(database),
+            (true, None) => Ok(Vec::new()),
+            (false, _) => self.reload(database).await,
+        }
+    }
+
+    pub async fn reload(
+        &mut self,
+        database: Option<&dyn DatabaseConnectable>,
+    ) -> Result<Vec<SettingsChange>, ConfigReport> {
+        let mut report = ConfigReport::default();
+
+        let mut layers = load_static_layers(&mut report);
+        layers.push(Arc::new(self.load_secrets_layer().await));
+        match database {
+            Some(database) => {
+                if let Some(layer) = report.take(load_database_layer(database)) {
+                    layers.push(Arc::new(layer));
+                }
+            }
+            None => layers.extend(self.find_layer(SettingSource::Database)),
+        }
+
+        if !report.is_empty() {
+            return Err(report);
         }
+
+        self.swap_layers(layers)
     }
 
-    fn load_structured_settings(&mut self) -> Result<()> {
-        self.load_hashmaps()?;
+    pub fn refresh_from_database(
+        &mut self,
+        database: &dyn DatabaseConnectable,
+    ) -> Result<Vec<SettingsChange>, ConfigReport> {
+        let layer: Arc<dyn SettingsLayer> = Arc::new(load_database_layer(database)?);
+
+        let layers = self
+            .layers
+            .iter()
+            .filter(|layer| layer.get_source() != SettingSource::Database)
+            .cloned()
+            .chain([layer])
+            .collect();
+
+        self.swap_layers(layers)
+    }
+
+    fn find_layer(&self, source: SettingSource) -> Option<Arc<dyn SettingsLayer>> {
+        self.layers
+            .iter()
+            .find(|layer| layer.get_source() == source)
+            .cloned()
+    }
+
+    // Nothing is replaced unless every setting resolves against the new layers
+    fn swap_layers(&mut self, layers: SettingsLayers) -> Result<Vec<SettingsChange>, ConfigReport> {
+        let mut report = ConfigReport::default();
+        let resolved = resolve_layers(&layers, &mut report);
+        if !report.is_empty() {
+            return Err(report);
+        }
+
+        self.layers = layers;
+        let changes = self.apply(resolved);
+
+        for change in changes.iter() {
+            info!("Setting changed {}", change);
+            for callback in self
+                .callbacks
+                .get(change.definition.key)
+                .into_iter()
+                .flatten()
+            {
+                callback(change);
+            }
+        }
+
+        Ok(changes)
+    }
+
+    // Readers keep the snapshot they loaded, the new one is visible to them all at once
+    fn apply(&mut self, resolved: ResolvedSettings) -> Vec<SettingsChange> {
+        let (next, changes) = self.settings.snapshot().with_resolved(resolved);
+        self.settings.store(next);
+
+        changes
+    }
+
+    async fn load_secrets_layer(&self) -> ValuesLayer {
+        let snapshot = self.snapshot();
+        let (Some(providers), Some(path)) = (
+            self.get_secrets_providers(),
+            snapshot.get_string(SETTING_SETTINGS_SECRETS_PATH),
+        ) else {
+            return ValuesLayer::empty(SettingSource::Secrets);
+        };
+
+        match providers.find_in_any(path).await {
+            Some(values) => {
+                info!(
+                    "Settings overrides loaded from Secrets Providers at \"{}\"",
+                    path
+                );
+                ValuesLayer::new(SettingSource::Secrets, values)
+            }
+            None => ValuesLayer::empty(SettingSource::Secrets),
+        }
+    }
+
+    async fn load_structured_settings(&mut self) -> Result<(), ConfigReport> {
+        self.load_hashmaps().await?;
 
         Ok(())
     }
 
-    fn load_hashmaps(&mut self) -> Result<()> {
-        self.load_data_providers()?;
+    async fn load_hashmaps(&mut self) -> Result<(), ConfigReport> {
+        self.load_data_providers().await?;
 
         // self.fetch_from_data_providers()?;
 
         Ok(())
     }
 
-    fn load_data_providers(&mut self) -> Result<()> {
-        self.load_secrets_providers()?;
+    async fn load_data_providers(&mut self) -> Result<(), ConfigReport> {
+        self.load_secrets_providers().await?;
 
         Ok(())
     }
 
-    fn load_secrets_providers(&mut self) -> Result<()> {
-        if let Some(use_providers) = self.bools.get(SETTING_USE_SECRETS_PROVIDER) {
-            if *use_providers && self.get_all_secrets_providers().is_empty() {
-                self.hashmaps.insert(
-                    SETTING_SECRETS_PROVIDERS,
-                    HashMapValueTypes::SecretsProviders(SecretsProviders::new()),
-                );
-            }
+    async fn load_secrets_providers(&mut self) -> Result<(), ConfigReport> {
+        let use_providers = self.get_bool(SETTING_USE_SECRETS_PROVIDER).unwrap_or(false);
+
+        if use_providers && self.get_all_secrets_providers().is_empty() {
+            // An invalid setting leaves the options unresolved and is reported by the next resolve
+            let Some(options) = self.get_secrets_options() else {
+                return Ok(());
+            };
+
+            let providers = SecretsProviders::load(&options).await?;
+            self.hashmaps.insert(
+                SETTING_SECRETS_PROVIDERS,
+                HashMapValueTypes::SecretsProviders(providers),
+            );
         }
 
         Ok(())
@@ -160,27 +358,273 @@ impl<'a> SettingsCache<'a> {
     }
 }
 
-fn load_env_var_settings(
-    bools: &mut LruSettingsCache<bool>,
-    ints: &mut LruSettingsCache<i32>,
-    strings: &mut LruSettingsCache<String>,
-) -> Result<()> {
-    for setting_type in APP_SETTINGS.iter() {
-        for setting in setting_type.iter() {
-            match setting {
-                SettingsTypes::Bool(name, var, value) => {
-                    bools.push(name, get_env_var(var, value.to_owned())?);
-                }
-                SettingsTypes::Int32(name, var, value) => {
-                    ints.push(name, get_env_var(var, value.to_owned())?);
-                }
-                SettingsTypes::String(name, var, value) => {
-                    strings.push(name, get_env_var(var, value.to_owned())?);
-                }
-                _ => (),
+fn resolve_layers(layers: &SettingsLayers, report: &mut ConfigReport) -> ResolvedSettings {
+    let layers: Vec<&dyn SettingsLayer> = layers.iter().map(AsRef::as_ref).collect();
+
+    APP_SETTINGS
+        .iter()
+        .copied()
+        .filter_map(|definition| {
+            let (value, source) = report.take(definition.resolve_from(&layers))?;
+            match definition.key == SETTING_HOST_ENVIRONMENT {
+                true => report.take(pin_environment(value, source)),
+                false => Some(value),
             }
+            .map(|value| (definition, value, source))
+        })
+        .collect()
+}
+
+// The environment is fixed at startup, other sources may only repeat it
+fn pin_environment(
+    value: SettingValue,
+    source: SettingSource,
+) -> Result<SettingValue, ConfigError> {
+    let environment = get_environment();
+    let requested = value.to_string();
+
+    match requested.parse::<Environment>() {
+        Ok(parsed) if parsed == environment => Ok(SettingValue::String(environment.to_string())),
+        _ => Err(ConfigError::InvalidSetting {
+            key: SETTING_HOST_ENVIRONMENT.to_owned(),
+            origin: source.to_string(),
+            value: requested,
+            reason: format!("the environment is fixed to {} at startup", environment),
+        }),
+    }
+}
+
+fn load_database_layer(database: &dyn DatabaseConnectable) -> Result<ValuesLayer, ConfigError> {
+    let rows = database
+        .get_connection()
+        .and_then(|mut connection| SystemConfig::get_settings(&mut connection))
+        .map_err(|err| ConfigError::SourceLoadFailed {
+            origin: SettingSource::Database.to_string(),
+            reason: err.to_string(),
+        })?;
+
+    for name in rows.keys() {
+        if !APP_SETTINGS.iter().any(|definition| definition.key == name) {
+            debug!("System config \"{}\" does not match any setting", name);
         }
     }
 
-    Ok(())
+    info!("Loaded {} system configs as settings", rows.len());
+    Ok(ValuesLayer::new(
+        SettingSource::Database,
+        values_from_map(rows),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use serde_json::{json, Value};
+    use tempfile::TempDir;
+    use tokio::runtime::Builder;
+
+    use crate::database::{
+        SETTING_DATABASE_MIGRATIONS_MODE, SETTING_DATABASE_POOL_CONNECTION_TIMEOUT,
+        SETTING_DATABASE_POOL_MAX_SIZE,
+    };
+    use crate::utils::environment::lock_test_env;
+
+    use super::consts::DATABASE_POOL_MAX_SIZE;
+    use super::registry::SettingValue;
+    use super::sources::{DefaultsLayer, ENV_CONFIG_FILE};
+    use super::*;
+
+    fn values_layer(source: SettingSource, values: Value) -> Arc<dyn SettingsLayer> {
+        Arc::new(ValuesLayer::new(source, values))
+    }
+
+    // The environment is pinned at startup, so the layers repeat whatever the tests run as
+    fn layers_with(config_file: Value, secrets: Value, database: Value) -> SettingsLayers {
+        vec![
+            Arc::new(DefaultsLayer),
+            values_layer(
+                SettingSource::ConfigFile,
+                json!({ SETTING_HOST_ENVIRONMENT: get_environment().to_string() }),
+            ),
+            values_layer(SettingSource::ConfigFile, config_file),
+            values_layer(SettingSource::Secrets, secrets),
+            values_layer(SettingSource::Database, database),
+        ]
+    }
+
+    fn cache_with(layers: SettingsLayers) -> SettingsCache {
+        let mut cache = SettingsCache {
+            settings: SettingsHandle::new(SettingsSnapshot::default()),
+            hashmaps: HashMap::new(),
+            layers: Vec::new(),
+            callbacks: HashMap::new(),
+        };
+        cache.swap_layers(layers).unwrap();
+
+        cache
+    }
+
+    #[test]
+    fn later_layers_take_precedence_and_are_tracked_as_the_source() {
+        let _lock = lock_test_env();
+        let cache = cache_with(layers_with(
+            json!({
+                "database": {"pool": {"max_size": 20, "connection_timeout": "10s"}},
+                "settings": {"reload_interval": "1m"},
+            }),
+            json!({
+                "database": {"pool": {"max_size": 30}},
+                "settings": {"reload_interval": "2m"},
+            }),
+            json!({ SETTING_DATABASE_POOL_MAX_SIZE: "40" }),
+        ));
+
+        assert_eq!(cache.get_int(SETTING_DATABASE_POOL_MAX_SIZE), Some(40));
+        assert_eq!(
+            cache.get_source(SETTING_DATABASE_POOL_MAX_SIZE),
+            Some(SettingSource::Database)
+        );
+        assert_eq!(
+            cache.get_duration(SETTING_SETTINGS_RELOAD_INTERVAL),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            cache.get_source(SETTING_SETTINGS_RELOAD_INTERVAL),
+            Some(SettingSource::Secrets)
+        );
+        assert_eq!(
+            cache.get_duration(SETTING_DATABASE_POOL_CONNECTION_TIMEOUT),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            cache.get_source(SETTING_DATABASE_POOL_CONNECTION_TIMEOUT),
+            Some(SettingSource::ConfigFile)
+        );
+        assert_eq!(
+            cache.get_string(SETTING_DATABASE_MIGRATIONS_MODE),
+            Some("auto".to_owned())
+        );
+        assert_eq!(
+            cache.get_source(SETTING_DATABASE_MIGRATIONS_MODE),
+            Some(SettingSource::Default)
+        );
+    }
+
+    #[test]
+    fn nothing_is_replaced_unless_every_setting_resolves() {
+        let _lock = lock_test_env();
+        let mut cache = cache_with(layers_with(
+            json!({"database": {"pool": {"max_size": 20}}}),
+            Value::Null,
+            Value::Null,
+        ));
+        let before = cache.snapshot();
+
+        let report = cache
+            .swap_layers(layers_with(
+                json!({"database": {"pool": {"max_size": 20, "connection_timeout": "5s"}}}),
+                Value::Null,
+                json!({ SETTING_DATABASE_POOL_MAX_SIZE: "0" }),
+            ))
+            .unwrap_err();
+
+        assert!(report.to_string().contains(SETTING_DATABASE_POOL_MAX_SIZE));
+        assert!(Arc::ptr_eq(&before, &cache.snapshot()));
+        assert_eq!(
+            cache
+                .snapshot()
+                .get(SETTING_DATABASE_POOL_CONNECTION_TIMEOUT),
+            Some(&SettingValue::Duration(Duration::from_secs(30)))
+        );
+        assert_eq!(
+            cache
+                .find_layer(SettingSource::Database)
+                .unwrap()
+                .get_raw(&DATABASE_POOL_MAX_SIZE),
+            None
+        );
+    }
+
+    #[test]
+    fn reloads_report_changes_to_the_callbacks() {
+        let _lock = lock_test_env();
+        let mut cache = cache_with(layers_with(
+            json!({"database": {"pool": {"max_size": 20}}}),
+            Value::Null,
+            Value::Null,
+        ));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        cache.on_change(SETTING_DATABASE_POOL_MAX_SIZE, move |change| {
+            recorder.lock().unwrap().push(change.current.clone());
+        });
+
+        let changes = cache
+            .swap_layers(layers_with(
+                json!({"database": {"pool": {"max_size": 20}}}),
+                Value::Null,
+                json!({ SETTING_DATABASE_POOL_MAX_SIZE: "25" }),
+            ))
+            .unwrap();
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].definition.key, SETTING_DATABASE_POOL_MAX_SIZE);
+        assert_eq!(changes[0].previous, Some(SettingValue::Int32(20)));
+        assert_eq!(changes[0].current, SettingValue::Int32(25));
+        assert_eq!(changes[0].source, SettingSource::Database);
+        assert_eq!(*seen.lock().unwrap(), vec![SettingValue::Int32(25)]);
+    }
+
+    #[test]
+    fn pending_reloads_reread_the_config_file() {
+        let directory = TempDir::new().unwrap();
+        let config_file = directory.path().join("celestus.toml");
+        fs::write(&config_file, "[database.pool]\nmax_size = 20\n").unwrap();
+
+        let _lock = lock_test_env();
+        env::set_var(ENV_CONFIG_FILE, &config_file);
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        runtime.block_on(async {
+            let mut cache = cache_with(load_static_layers(&mut ConfigReport::default()));
+            let counter = calls.clone();
+            cache.on_change(SETTING_DATABASE_POOL_MAX_SIZE, move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+            let reloader = SettingsReloader::start(None, None);
+
+            fs::write(&config_file, "[database.pool]\nmax_size = 25\n").unwrap();
+            reloader.trigger(ReloadTrigger::Manual);
+            let changes = cache.reload_pending(&reloader, None).await.unwrap();
+            assert_eq!(changes.len(), 1);
+            assert_eq!(cache.get_int(SETTING_DATABASE_POOL_MAX_SIZE), Some(25));
+            assert_eq!(
+                cache.get_source(SETTING_DATABASE_POOL_MAX_SIZE),
+                Some(SettingSource::ConfigFile)
+            );
+
+            // One invalid setting keeps the valid change next to it from being applied too
+            fs::write(
+                &config_file,
+                "[database.pool]\nmax_size = 0\nconnection_timeout = \"5s\"\n",
+            )
+            .unwrap();
+            reloader.trigger(ReloadTrigger::Manual);
+            assert!(cache.reload_pending(&reloader, None).await.is_err());
+            assert_eq!(cache.get_int(SETTING_DATABASE_POOL_MAX_SIZE), Some(25));
+            assert_eq!(
+                cache.get_duration(SETTING_DATABASE_POOL_CONNECTION_TIMEOUT),
+                Some(Duration::from_secs(30))
+            );
+        });
+        env::remove_var(ENV_CONFIG_FILE);
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
 }
@@ -0,0 +1,308 @@
+use std::fmt::{self, Display, Write};
+use std::time::Duration;
+
+use regex::Regex;
+use serde_json::Value;
+use strum_macros::Display;
+use url::Url;
+
+use crate::utils::errors::ConfigError;
+
+use super::sources::{DefaultsLayer, EnvironmentLayer, SettingSource, SettingsLayer};
+use super::values::{
+    format_byte_size, format_duration, parse_byte_size, parse_duration, parse_list,
+};
+
+pub const REDACTED: &str = "***";
+
+#[derive(Clone, Copy, Debug, PartialEq, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum SettingKind {
+    Bool,
+    Int32,
+    Int64,
+    UInt64,
+    Float,
+    String,
+    Duration,
+    ByteSize,
+    List,
+    Url,
+    Enum(&'static [&'static str]),
+    Json,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Validation {
+    None,
+    Range { min: i64, max: i64 },
+    DurationRange { min: Duration, max: Duration },
+    OneOf(&'static [&'static str]),
+    Pattern(&'static str),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SettingValue {
+    Bool(bool),
+    Int32(i32),
+    Int64(i64),
+    UInt64(u64),
+    Float(f64),
+    String(String),
+    Duration(Duration),
+    ByteSize(u64),
+    List(Vec<String>),
+    Url(Url),
+    Json(Value),
+}
+
+#[derive(Debug)]
+pub struct SettingDefinition {
+    pub key: &'static str,
+    pub env: &'static str,
+    pub kind: SettingKind,
+    pub default: Option<&'static str>,
+    pub description: &'static str,
+    pub validation: Validation,
+    pub secret: bool,
+}
+
+pub type SettingsRegistry = &'static [&'static SettingDefinition];
+
+#[macro_export]
+macro_rules! settings {
+    ($registry:ident { $($name:ident { $($field:ident: $value:expr),* $(,)? })* }) => {
+        $(
+            pub const $name: $crate::cache::settings::registry::SettingDefinition =
+                $crate::cache::settings::registry::SettingDefinition { $($field: $value),* };
+        )*
+
+        pub const $registry: $crate::cache::settings::registry::SettingsRegistry = &[$(&$name),*];
+    };
+}
+
+impl Display for SettingValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingValue::Bool(value) => write!(f, "{}", value),
+            SettingValue::Int32(value) => write!(f, "{}", value),
+            SettingValue::Int64(value) => write!(f, "{}", value),
+            SettingValue::UInt64(value) => write!(f, "{}", value),
+            SettingValue::Float(value) => write!(f, "{}", value),
+            SettingValue::String(value) => write!(f, "{}", value),
+            SettingValue::Duration(value) => write!(f, "{}", format_duration(value)),
+            SettingValue::ByteSize(value) => write!(f, "{}", format_byte_size(*value)),
+            SettingValue::List(values) => write!(f, "{}", values.join(",")),
+            SettingValue::Url(value) => write!(f, "{}", value),
+            SettingValue::Json(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl Display for Validation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Validation::None => Ok(()),
+            Validation::Range { min, max } => write!(f, "{}..={}", min, max),
+            Validation::DurationRange { min, max } => {
+                write!(f, "{}..={}", format_duration(min), format_duration(max))
+            }
+            Validation::OneOf(values) => write!(f, "one of {}", values.join(", ")),
+            Validation::Pattern(pattern) => write!(f, "matches {}", pattern),
+        }
+    }
+}
+
+impl SettingDefinition {
+    pub fn resolve(&self) -> Result<SettingValue, ConfigError> {
+        self.resolve_from(&[&DefaultsLayer, &EnvironmentLayer])
+            .map(|(value, _)| value)
+    }
+
+    pub fn resolve_from(
+        &self,
+        layers: &[&dyn SettingsLayer],
+    ) -> Result<(SettingValue, SettingSource), ConfigError> {
+        let Some((raw, source)) = layers
+            .iter()
+            .rev()
+            .find_map(|layer| Some((layer.get_raw(self)?, layer.get_source())))
+        else {
+            return Err(ConfigError::MissingVariable {
+                name: self.env.to_owned(),
+            });
+        };
+
+        let value = self
+            .parse(raw.trim())
+            .and_then(|value| self.validate(&value).map(|_| value))
+            .map_err(|err| match (err, source) {
+                (err, SettingSource::Environment | SettingSource::Default) => err,
+                (ConfigError::InvalidValue { value, reason, .. }, source) => {
+                    ConfigError::InvalidSetting {
+                        key: self.key.to_owned(),
+                        origin: source.to_string(),
+                        value,
+                        reason,
+                    }
+                }
+                (err, _) => err,
+            })?;
+
+        Ok((value, source))
+    }
+
+    pub fn display_value(&self, value: &SettingValue) -> String {
+        match self.secret {
+            true => REDACTED.to_owned(),
+            false => redact_url_password(&value.to_string()),
+        }
+    }
+
+    fn parse(&self, raw: &str) -> Result<SettingValue, ConfigError> {
+        let parsed = match self.kind {
+            SettingKind::Bool => raw
+                .to_lowercase()
+                .parse::<bool>()
+                .map(SettingValue::Bool)
+                .map_err(|err| err.to_string()),
+            SettingKind::Int32 => raw
+                .parse::<i32>()
+                .map(SettingValue::Int32)
+                .map_err(|err| err.to_string()),
+            SettingKind::Int64 => raw
+                .parse::<i64>()
+                .map(SettingValue::Int64)
+                .map_err(|err| err.to_string()),
+            SettingKind::UInt64 => raw
+                .parse::<u64>()
+                .map(SettingValue::UInt64)
+                .map_err(|err| err.to_string()),
+            SettingKind::Float => raw
+                .parse::<f64>()
+                .map(SettingValue::Float)
+                .map_err(|err| err.to_string()),
+            SettingKind::String => Ok(SettingValue::String(raw.to_owned())),
+            SettingKind::Duration => parse_duration(raw).map(SettingValue::Duration),
+            SettingKind::ByteSize => parse_byte_size(raw).map(SettingValue::ByteSize),
+            SettingKind::List => Ok(SettingValue::List(parse_list(raw))),
+            SettingKind::Url => Url::parse(raw)
+                .map(SettingValue::Url)
+                .map_err(|err| err.to_string()),
+            SettingKind::Enum(variants) => variants
+                .iter()
+                .find(|variant| variant.eq_ignore_ascii_case(raw))
+                .map(|variant| SettingValue::String(variant.to_string()))
+                .ok_or_else(|| format!("expected one of {}", variants.join(", "))),
+            SettingKind::Json => serde_json::from_str(raw)
+                .map(SettingValue::Json)
+                .map_err(|err| err.to_string()),
+        };
+
+        parsed.map_err(|reason| self.invalid(raw, reason))
+    }
+
+    fn validate(&self, value: &SettingValue) -> Result<(), ConfigError> {
+        let valid = match (&self.validation, value) {
+            (Validation::None, _) => true,
+            (Validation::Range { min, max }, SettingValue::Int32(number)) => {
+                (*min..=*max).contains(&i64::from(*number))
+            }
+            (Validation::Range { min, max }, SettingValue::Int64(number)) => {
+                (*min..=*max).contains(number)
+            }
+            (
+                Validation::Range { min, max },
+                SettingValue::UInt64(number) | SettingValue::ByteSize(number),
+            ) => i64::try_from(*number).is_ok_and(|number| (*min..=*max).contains(&number)),
+            (Validation::DurationRange { min, max }, SettingValue::Duration(duration)) => {
+                (*min..=*max).contains(duration)
+            }
+            (Validation::OneOf(allowed), SettingValue::String(text)) => is_one_of(allowed, text),
+            (Validation::OneOf(allowed), SettingValue::List(items)) => {
+                items.iter().all(|item| is_one_of(allowed, item))
+            }
+            (Validation::Pattern(pattern), SettingValue::String(text)) => {
+                self.matches(pattern, text)?
+            }
+            (Validation::Pattern(pattern), SettingValue::List(items)) => {
+                let mut valid = true;
+                for item in items.iter() {
+                    valid &= self.matches(pattern, item)?;
+                }
+                valid
+            }
+            _ => true,
+        };
+
+        match valid {
+            true => Ok(()),
+            false => Err(self.invalid(
+                &self.display_value(value),
+                format!("expected {}", self.validation),
+            )),
+        }
+    }
+
+    fn matches(&self, pattern: &str, text: &str) -> Result<bool, ConfigError> {
+        Regex::new(pattern)
+            .map(|regex| regex.is_match(text))
+            .map_err(|err| self.invalid(text, format!("invalid pattern {}", err)))
+    }
+
+    fn invalid(&self, raw: &str, reason: String) -> ConfigError {
+        ConfigError::InvalidValue {
+            name: self.env.to_owned(),
+            value: match self.secret {
+                true => REDACTED.to_owned(),
+                false => raw.to_owned(),
+            },
+            reason,
+        }
+    }
+}
+
+// Connection URLs can carry a password even in settings that are not secret
+pub fn redact_url_password(value: &str) -> String {
+    match Url::parse(value) {
+        Ok(mut url) if url.password().is_some() => {
+            let _ = url.set_password(Some(REDACTED));
+            url.to_string()
+        }
+        _ => value.to_owned(),
+    }
+}
+
+fn is_one_of(allowed: &[&str], text: &str) -> bool {
+    allowed
+        .iter()
+        .any(|candidate| candidate.eq_ignore_ascii_case(text))
+}
+
+pub fn render_markdown(registry: SettingsRegistry) -> String {
+    let mut output = String::from(
+        "# Settings\n\n\
+        | Key | Environment variable | Type | Default | Validation | Secret | Description |\n\
+        | --- | --- | --- | --- | --- | --- | --- |\n",
+    );
+
+    for definition in registry.iter() {
+        let kind = match definition.kind {
+            SettingKind::Enum(variants) => format!("enum ({})", variants.join(", ")),
+            kind => kind.to_string(),
+        };
+        let _ = writeln!(
+            output,
+            "| {} | {} | {} | {} | {} | {} | {} |",
+            definition.key,
+            definition.env,
+            kind,
+            definition.default.unwrap_or("required"),
+            definition.validation,
+            if definition.secret { "yes" } else { "no" },
+            definition.description
+        );
+    }
+
+    output
+}
@@ -0,0 +1,186 @@
+use std::fmt::{self, Display};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crossbeam::channel::{unbounded, Receiver, Sender};
+use log::{info, warn};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use strum_macros::Display;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+use crate::utils::runtime::get_handle;
+
+use super::registry::{SettingDefinition, SettingValue};
+use super::sources::SettingSource;
+
+pub const SETTING_SETTINGS_RELOAD_INTERVAL: &str = "settings.reload_interval";
+pub const ENV_SETTINGS_RELOAD_INTERVAL: &str = "SETTINGS_RELOAD_INTERVAL";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum ReloadTrigger {
+    Signal,
+    ConfigFile,
+    DatabasePoll,
+    Manual,
+}
+
+#[derive(Clone, Debug)]
+pub struct SettingsChange {
+    pub definition: &'static SettingDefinition,
+    pub previous: Option<SettingValue>,
+    pub current: SettingValue,
+    pub source: SettingSource,
+}
+
+pub type SettingsCallback = Box<dyn Fn(&SettingsChange) + Send + Sync>;
+
+impl Display for SettingsChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let previous = match &self.previous {
+            Some(value) => self.definition.display_value(value),
+            None => String::from("<unset>"),
+        };
+
+        write!(
+            f,
+            "{}: {} -> {} [{}]",
+            self.definition.key,
+            previous,
+            self.definition.display_value(&self.current),
+            self.source
+        )
+    }
+}
+
+pub struct SettingsReloader {
+    sender: Sender<ReloadTrigger>,
+    triggers: Receiver<ReloadTrigger>,
+    workers: Vec<JoinHandle<()>>,
+    file_watcher: Option<RecommendedWatcher>,
+}
+
+impl SettingsReloader {
+    pub fn start(poll_interval: Option<Duration>, config_file: Option<PathBuf>) -> Self {
+        let (sender, triggers) = unbounded();
+
+        let mut workers = Vec::new();
+        #[cfg(unix)]
+        workers.push(spawn_signal_worker(sender.clone()));
+        if let Some(interval) = poll_interval {
+            workers.push(spawn_poll_worker(interval, sender.clone()));
+        }
+
+        let file_watcher = config_file.and_then(|path| watch_config_file(path, sender.clone()));
+
+        Self {
+            sender,
+            triggers,
+            workers,
+            file_watcher,
+        }
+    }
+
+    pub fn get_triggers(&self) -> &Receiver<ReloadTrigger> {
+        &self.triggers
+    }
+
+    pub fn trigger(&self, trigger: ReloadTrigger) {
+        let _ = self.sender.send(trigger);
+    }
+
+    // The workers only queue triggers, nothing is reloaded until a caller polls them here,
+    // usually through SettingsCache::reload_pending
+    pub fn take_pending(&self) -> Vec<ReloadTrigger> {
+        self.triggers.try_iter().collect()
+    }
+
+    pub fn stop(&mut self) {
+        for worker in self.workers.drain(..) {
+            worker.abort();
+        }
+        self.file_watcher = None;
+    }
+}
+
+impl Drop for SettingsReloader {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(unix)]
+fn spawn_signal_worker(sender: Sender<ReloadTrigger>) -> JoinHandle<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    get_handle().spawn(async move {
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(err) => {
+                warn!("Settings will not be reloaded on SIGHUP: {}", err);
+                return;
+            }
+        };
+
+        while hangups.recv().await.is_some() {
+            info!("SIGHUP received, reloading settings");
+            if sender.send(ReloadTrigger::Signal).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+fn spawn_poll_worker(interval: Duration, sender: Sender<ReloadTrigger>) -> JoinHandle<()> {
+    get_handle().spawn(async move {
+        loop {
+            sleep(interval).await;
+            if sender.send(ReloadTrigger::DatabasePoll).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+fn watch_config_file(path: PathBuf, sender: Sender<ReloadTrigger>) -> Option<RecommendedWatcher> {
+    let file_name = path.file_name()?.to_owned();
+    // Editors usually replace the file instead of writing to it, so watch its directory
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let handler = move |event: notify::Result<Event>| match event {
+        Ok(event)
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                && event
+                    .paths
+                    .iter()
+                    .any(|changed| changed.file_name() == Some(file_name.as_os_str())) =>
+        {
+            let _ = sender.send(ReloadTrigger::ConfigFile);
+        }
+        Ok(_) => (),
+        Err(err) => warn!("Config file watcher failed: {}", err),
+    };
+
+    let mut watcher = match notify::recommended_watcher(handler) {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            warn!("Config file will not be watched for changes: {}", err);
+            return None;
+        }
+    };
+    if let Err(err) = watcher.watch(&directory, RecursiveMode::NonRecursive) {
+        warn!(
+            "Config file {} will not be watched for changes: {}",
+            path.display(),
+            err
+        );
+        return None;
+    }
+
+    info!("Watching {} for settings changes", path.display());
+    Some(watcher)
+}
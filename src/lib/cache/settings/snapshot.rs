@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use serde::de::DeserializeOwned;
+use url::Url;
+
+use super::registry::{SettingDefinition, SettingValue};
+use super::reload::SettingsChange;
+use super::sources::SettingSource;
+use super::view::SettingsView;
+
+#[derive(Clone, Debug, Default)]
+pub struct SettingsSnapshot {
+    values: HashMap<&'static str, SettingValue>,
+    sources: HashMap<&'static str, SettingSource>,
+}
+
+impl SettingsSnapshot {
+    pub fn get(&self, key: &str) -> Option<&SettingValue> {
+        self.values.get(key)
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.get(key)? {
+            SettingValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_int(&self, key: &str) -> Option<i32> {
+        match self.get(key)? {
+            SettingValue::Int32(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        match self.get(key)? {
+            SettingValue::Int32(value) => Some(i64::from(*value)),
+            SettingValue::Int64(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_u64(&self, key: &str) -> Option<u64> {
+        match self.get(key)? {
+            SettingValue::UInt64(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_float(&self, key: &str) -> Option<f64> {
+        match self.get(key)? {
+            SettingValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_string(&self, key: &str) -> Option<&str> {
+        match self.get(key)? {
+            SettingValue::String(value) => Some(value.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn get_duration(&self, key: &str) -> Option<Duration> {
+        match self.get(key)? {
+            SettingValue::Duration(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_byte_size(&self, key: &str) -> Option<u64> {
+        match self.get(key)? {
+            SettingValue::ByteSize(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_list(&self, key: &str) -> Option<&[String]> {
+        match self.get(key)? {
+            SettingValue::List(values) => Some(values.as_slice()),
+            _ => None,
+        }
+    }
+
+    pub fn get_url(&self, key: &str) -> Option<&Url> {
+        match self.get(key)? {
+            SettingValue::Url(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_enum<Enum: FromStr>(&self, key: &str) -> Option<Enum> {
+        self.get_string(key)?.parse().ok()
+    }
+
+    pub fn get_json<Data: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Option<Result<Data, serde_json::Error>> {
+        match self.get(key)? {
+            SettingValue::Json(value) => Some(serde_json::from_value(value.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn get_source(&self, key: &str) -> Option<SettingSource> {
+        self.sources.get(key).copied()
+    }
+
+    pub fn get_values(&self) -> &HashMap<&'static str, SettingValue> {
+        &self.values
+    }
+
+    pub(super) fn with_resolved(
+        &self,
+        resolved: Vec<(&'static SettingDefinition, SettingValue, SettingSource)>,
+    ) -> (Self, Vec<SettingsChange>) {
+        let mut next = self.clone();
+        let mut changes = Vec::new();
+
+        for (definition, value, source) in resolved {
+            next.sources.insert(definition.key, source);
+            let previous = next.values.insert(definition.key, value.clone());
+            if previous.as_ref() != Some(&value) {
+                changes.push(SettingsChange {
+                    definition,
+                    previous,
+                    current: value,
+                    source,
+                });
+            }
+        }
+
+        (next, changes)
+    }
+}
+
+#[derive(Clone)]
+pub struct SettingsHandle {
+    current: Arc<ArcSwap<SettingsSnapshot>>,
+}
+
+impl SettingsHandle {
+    pub(super) fn new(snapshot: SettingsSnapshot) -> Self {
+        Self {
+            current: Arc::new(ArcSwap::from_pointee(snapshot)),
+        }
+    }
+
+    pub fn snapshot(&self) -> Arc<SettingsSnapshot> {
+        self.current.load_full()
+    }
+
+    pub fn scope(&self, prefix: &str) -> SettingsView {
+        SettingsView::new(self.clone(), prefix)
+    }
+
+    pub(super) fn store(&self, snapshot: SettingsSnapshot) {
+        self.current.store(Arc::new(snapshot));
+    }
+}
+
+impl SettingsReader for SettingsHandle {
+    fn read<Output>(
+        &self,
+        key: &str,
+        reader: impl FnOnce(&SettingsSnapshot, &str) -> Output,
+    ) -> Output {
+        reader(&self.current.load(), key)
+    }
+}
+
+pub trait SettingsReader {
+    fn read<Output>(
+        &self,
+        key: &str,
+        reader: impl FnOnce(&SettingsSnapshot, &str) -> Output,
+    ) -> Output;
+
+    fn get_bool(&self, key: &str) -> Option<bool> {
+        self.read(key, SettingsSnapshot::get_bool)
+    }
+
+    fn get_int(&self, key: &str) -> Option<i32> {
+        self.read(key, SettingsSnapshot::get_int)
+    }
+
+    fn get_i64(&self, key: &str) -> Option<i64> {
+        self.read(key, SettingsSnapshot::get_i64)
+    }
+
+    fn get_u64(&self, key: &str) -> Option<u64> {
+        self.read(key, SettingsSnapshot::get_u64)
+    }
+
+    fn get_float(&self, key: &str) -> Option<f64> {
+        self.read(key, SettingsSnapshot::get_float)
+    }
+
+    fn get_string(&self, key: &str) -> Option<String> {
+        self.read(key, |snapshot, key| {
+            snapshot.get_string(key).map(str::to_owned)
+        })
+    }
+
+    fn get_duration(&self, key: &str) -> Option<Duration> {
+        self.read(key, SettingsSnapshot::get_duration)
+    }
+
+    fn get_byte_size(&self, key: &str) -> Option<u64> {
+        self.read(key, SettingsSnapshot::get_byte_size)
+    }
+
+    fn get_list(&self, key: &str) -> Option<Vec<String>> {
+        self.read(key, |snapshot, key| {
+            snapshot.get_list(key).map(<[String]>::to_vec)
+        })
+    }
+
+    fn get_url(&self, key: &str) -> Option<Url> {
+        self.read(key, |snapshot, key| snapshot.get_url(key).cloned())
+    }
+
+    fn get_enum<Enum: FromStr>(&self, key: &str) -> Option<Enum> {
+        self.read(key, SettingsSnapshot::get_enum)
+    }
+
+    fn get_json<Data: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Option<Result<Data, serde_json::Error>> {
+        self.read(key, SettingsSnapshot::get_json)
+    }
+
+    fn get_source(&self, key: &str) -> Option<SettingSource> {
+        self.read(key, SettingsSnapshot::get_source)
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::Value;
+use strum_macros::Display;
+
+use crate::utils::environment::get_env_var_or_file;
+use crate::utils::errors::{ConfigError, ConfigReport};
+
+use super::registry::{SettingDefinition, SettingKind};
+
+pub const ENV_CONFIG_FILE: &str = "CELESTUS_CONFIG";
+pub const SETTING_SETTINGS_SECRETS_PATH: &str = "settings.secrets_path";
+pub const ENV_SETTINGS_SECRETS_PATH: &str = "SETTINGS_SECRETS_PATH";
+
+const DEFAULT_CONFIG_FILES: [&str; 3] = ["celestus.toml", "celestus.yaml", "celestus.yml"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum SettingSource {
+    Default,
+    ConfigFile,
+    Environment,
+    Secrets,
+    Database,
+}
+
+pub trait SettingsLayer: Send + Sync {
+    fn get_source(&self) -> SettingSource;
+
+    fn get_raw(&self, definition: &SettingDefinition) -> Option<String>;
+}
+
+pub struct DefaultsLayer;
+
+impl SettingsLayer for DefaultsLayer {
+    fn get_source(&self) -> SettingSource {
+        SettingSource::Default
+    }
+
+    fn get_raw(&self, definition: &SettingDefinition) -> Option<String> {
+        definition.default.map(str::to_owned)
+    }
+}
+
+pub struct EnvironmentLayer;
+
+impl SettingsLayer for EnvironmentLayer {
+    fn get_source(&self) -> SettingSource {
+        SettingSource::Environment
+    }
+
+    fn get_raw(&self, definition: &SettingDefinition) -> Option<String> {
+        get_env_var_or_file(definition.env).ok()
+    }
+}
+
+pub struct ValuesLayer {
+    source: SettingSource,
+    values: Value,
+}
+
+impl ValuesLayer {
+    pub fn new(source: SettingSource, values: Value) -> Self {
+        Self { source, values }
+    }
+
+    pub fn empty(source: SettingSource) -> Self {
+        Self::new(source, Value::Null)
+    }
+
+    pub fn from_config_file() -> Result<Self, ConfigError> {
+        match find_config_file() {
+            Some(path) => Ok(Self::new(
+                SettingSource::ConfigFile,
+                read_config_file(&path)?,
+            )),
+            None => Ok(Self::empty(SettingSource::ConfigFile)),
+        }
+    }
+
+    pub fn get_values(&self) -> &Value {
+        &self.values
+    }
+}
+
+impl SettingsLayer for ValuesLayer {
+    fn get_source(&self) -> SettingSource {
+        self.source
+    }
+
+    fn get_raw(&self, definition: &SettingDefinition) -> Option<String> {
+        let value = match self.values.get(definition.key) {
+            Some(value) => value,
+            None => definition
+                .key
+                .split('.')
+                .try_fold(&self.values, |node, segment| node.get(segment))?,
+        };
+
+        match definition.kind {
+            SettingKind::Json if !value.is_null() => Some(value.to_string()),
+            _ => value_to_raw(value),
+        }
+    }
+}
+
+pub type SettingsLayers = Vec<Arc<dyn SettingsLayer>>;
+
+pub fn find_config_file() -> Option<PathBuf> {
+    match env::var(ENV_CONFIG_FILE) {
+        Ok(path) => Some(PathBuf::from(path)),
+        Err(_) => DEFAULT_CONFIG_FILES
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.is_file()),
+    }
+}
+
+pub fn load_static_layers(report: &mut ConfigReport) -> SettingsLayers {
+    let config_file = report
+        .take(ValuesLayer::from_config_file())
+        .unwrap_or_else(|| ValuesLayer::empty(SettingSource::ConfigFile));
+
+    vec![
+        Arc::new(DefaultsLayer),
+        Arc::new(config_file),
+        Arc::new(EnvironmentLayer),
+    ]
+}
+
+pub fn value_to_raw(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        Value::Array(items) => Some(
+            items
+                .iter()
+                .filter_map(value_to_raw)
+                .collect::<Vec<String>>()
+                .join(","),
+        ),
+        other => Some(other.to_string()),
+    }
+}
+
+pub fn values_from_map(values: HashMap<String, Value>) -> Value {
+    Value::Object(values.into_iter().collect())
+}
+
+fn read_config_file(path: &Path) -> Result<Value, ConfigError> {
+    let failed = |reason: String| ConfigError::ConfigFileFailed {
+        path: path.display().to_string(),
+        reason,
+    };
+
+    let contents = fs::read_to_string(path).map_err(|err| failed(err.to_string()))?;
+
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("yaml") | Some("yml") => {
+            serde_yaml::from_str(&contents).map_err(|err| failed(err.to_string()))
+        }
+        _ => toml::from_str(&contents).map_err(|err| failed(err.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::cache::settings::consts::{
+        DATABASE_POOL_CONNECTION_TIMEOUT, DATABASE_POOL_MAX_SIZE, SECRETS_PROVIDERS,
+    };
+
+    use super::*;
+
+    #[test]
+    fn values_are_found_by_dotted_key_or_nested_tables() {
+        let layer = ValuesLayer::new(
+            SettingSource::ConfigFile,
+            json!({
+                "database.pool.max_size": 20,
+                "database": {"pool": {"max_size": 30, "connection_timeout": "10s"}},
+            }),
+        );
+
+        // A dotted key wins over the nested table holding the same setting
+        assert_eq!(
+            layer.get_raw(&DATABASE_POOL_MAX_SIZE),
+            Some("20".to_owned())
+        );
+        assert_eq!(
+            layer.get_raw(&DATABASE_POOL_CONNECTION_TIMEOUT),
+            Some("10s".to_owned())
+        );
+        assert_eq!(layer.get_raw(&SECRETS_PROVIDERS), None);
+    }
+
+    #[test]
+    fn values_are_converted_to_raw_strings() {
+        let layer = ValuesLayer::new(
+            SettingSource::Secrets,
+            json!({"secrets": {"providers": ["vault", "file"]}, "database.pool.max_size": null}),
+        );
+
+        assert_eq!(
+            layer.get_raw(&SECRETS_PROVIDERS),
+            Some("vault,file".to_owned())
+        );
+        assert_eq!(layer.get_raw(&DATABASE_POOL_MAX_SIZE), None);
+        assert_eq!(
+            ValuesLayer::empty(SettingSource::Database).get_raw(&DATABASE_POOL_MAX_SIZE),
+            None
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+const BINARY_UNITS: [(&str, u64); 4] = [
+    ("TiB", 1 << 40),
+    ("GiB", 1 << 30),
+    ("MiB", 1 << 20),
+    ("KiB", 1 << 10),
+];
+
+// Bare numbers are seconds, so integer settings can become durations without breaking them
+pub fn parse_duration(raw: &str) -> Result<Duration, String> {
+    match raw.parse::<u64>() {
+        Ok(seconds) => Ok(Duration::from_secs(seconds)),
+        Err(_) => humantime::parse_duration(raw).map_err(|err| err.to_string()),
+    }
+}
+
+pub fn format_duration(duration: &Duration) -> String {
+    humantime::format_duration(*duration).to_string()
+}
+
+pub fn parse_byte_size(raw: &str) -> Result<u64, String> {
+    let lowercase = raw.to_lowercase();
+    let split = lowercase
+        .find(|character: char| !character.is_ascii_digit() && character != '.')
+        .unwrap_or(lowercase.len());
+    let (number, unit) = lowercase.split_at(split);
+
+    let multiplier: u64 = match unit.trim() {
+        "" | "b" => 1,
+        "k" | "kb" => 1_000,
+        "ki" | "kib" => 1 << 10,
+        "m" | "mb" => 1_000_000,
+        "mi" | "mib" => 1 << 20,
+        "g" | "gb" => 1_000_000_000,
+        "gi" | "gib" => 1 << 30,
+        "t" | "tb" => 1_000_000_000_000,
+        "ti" | "tib" => 1 << 40,
+        other => return Err(format!("unknown size unit \"{}\"", other)),
+    };
+
+    match number.parse::<u64>() {
+        Ok(whole) => whole
+            .checked_mul(multiplier)
+            .ok_or_else(|| String::from("size is too large")),
+        Err(_) => {
+            let fraction = number
+                .parse::<f64>()
+                .map_err(|_| format!("invalid size \"{}\"", raw))?;
+            let bytes = (fraction * multiplier as f64).round();
+
+            // Casting would saturate instead of failing like the whole number case
+            match bytes < u64::MAX as f64 {
+                true => Ok(bytes as u64),
+                false => Err(String::from("size is too large")),
+            }
+        }
+    }
+}
+
+// is_multiple_of needs Rust 1.87, the Docker image builds with 1.75
+#[allow(clippy::manual_is_multiple_of)]
+pub fn format_byte_size(bytes: u64) -> String {
+    BINARY_UNITS
+        .iter()
+        .find(|(_, size)| bytes >= *size && bytes % size == 0)
+        .map(|(unit, size)| format!("{}{}", bytes / size, unit))
+        .unwrap_or_else(|| format!("{}B", bytes))
+}
+
+pub fn parse_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_default_to_seconds() {
+        assert_eq!(parse_duration("0"), Ok(Duration::ZERO));
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("1m 30s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn durations_reject_unknown_units_and_overflow() {
+        assert!(parse_duration("5 fortnights").is_err());
+        assert!(parse_duration("-5s").is_err());
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("99999999999999999999s").is_err());
+    }
+
+    #[test]
+    fn byte_sizes_accept_decimal_and_binary_units() {
+        assert_eq!(parse_byte_size("512"), Ok(512));
+        assert_eq!(parse_byte_size("512B"), Ok(512));
+        assert_eq!(parse_byte_size("2kb"), Ok(2_000));
+        assert_eq!(parse_byte_size("2 KiB"), Ok(2_048));
+        assert_eq!(parse_byte_size("3GiB"), Ok(3 << 30));
+    }
+
+    #[test]
+    fn byte_sizes_accept_fractions() {
+        assert_eq!(parse_byte_size("1.5KiB"), Ok(1_536));
+        assert_eq!(parse_byte_size("1.5k"), Ok(1_500));
+        assert_eq!(parse_byte_size("0.5MiB"), Ok(1 << 19));
+        assert_eq!(parse_byte_size("0.5"), Ok(1));
+    }
+
+    #[test]
+    fn byte_sizes_reject_overflow() {
+        assert!(parse_byte_size("20000000TiB").is_err());
+        assert!(parse_byte_size("20000000.5TiB").is_err());
+        assert!(parse_byte_size("18446744073709551616").is_err());
+    }
+
+    #[test]
+    fn byte_sizes_reject_unknown_units() {
+        assert!(parse_byte_size("5 PiB").is_err());
+        assert!(parse_byte_size("5 bytes").is_err());
+        assert!(parse_byte_size("-5").is_err());
+        assert!(parse_byte_size("1.2.3k").is_err());
+        assert!(parse_byte_size("").is_err());
+    }
+
+    #[test]
+    fn byte_sizes_round_trip_through_format() {
+        for bytes in [0, 1, 1_000, 1 << 10, 3 << 30, (1 << 40) + 1] {
+            assert_eq!(parse_byte_size(&format_byte_size(bytes)), Ok(bytes));
+        }
+    }
+
+    #[test]
+    fn lists_skip_blank_items() {
+        assert_eq!(
+            parse_list("vault, files ,,mounted,"),
+            ["vault", "files", "mounted"]
+        );
+        assert!(parse_list("").is_empty());
+        assert!(parse_list(" , ").is_empty());
+    }
+}
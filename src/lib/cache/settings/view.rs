@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use super::snapshot::{SettingsHandle, SettingsReader, SettingsSnapshot};
+
+pub const KEY_SEPARATOR: char = '.';
+
+#[derive(Clone)]
+pub struct SettingsView {
+    handle: SettingsHandle,
+    prefix: String,
+}
+
+impl SettingsView {
+    pub(super) fn new(handle: SettingsHandle, prefix: &str) -> Self {
+        Self {
+            handle,
+            prefix: prefix.trim_matches(KEY_SEPARATOR).to_owned(),
+        }
+    }
+
+    pub fn get_prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn scope(&self, prefix: &str) -> SettingsView {
+        SettingsView::new(self.handle.clone(), &self.to_absolute(prefix))
+    }
+
+    pub fn snapshot(&self) -> Arc<SettingsSnapshot> {
+        self.handle.snapshot()
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .snapshot()
+            .get_values()
+            .keys()
+            .filter_map(|key| self.to_relative(key))
+            .map(str::to_owned)
+            .collect();
+        keys.sort();
+
+        keys
+    }
+
+    fn to_absolute(&self, key: &str) -> String {
+        match self.prefix.is_empty() {
+            true => key.to_owned(),
+            false => format!("{}{}{}", self.prefix, KEY_SEPARATOR, key),
+        }
+    }
+
+    fn to_relative<'key>(&self, key: &'key str) -> Option<&'key str> {
+        match self.prefix.is_empty() {
+            true => Some(key),
+            false => key
+                .strip_prefix(self.prefix.as_str())?
+                .strip_prefix(KEY_SEPARATOR),
+        }
+    }
+}
+
+impl SettingsReader for SettingsView {
+    fn read<Output>(
+        &self,
+        key: &str,
+        reader: impl FnOnce(&SettingsSnapshot, &str) -> Output,
+    ) -> Output {
+        self.handle.read(&self.to_absolute(key), reader)
+    }
+}
@@ -1,9 +1,22 @@
+use diesel::pg::PgConnection;
+use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use log::error;
+
+use super::errors::DatabaseError;
+
+pub type PgPool = Pool<ConnectionManager<PgConnection>>;
+pub type PgPooledConnection = PooledConnection<ConnectionManager<PgConnection>>;
+
 pub trait DatabaseConnectable {
-    // fn get_connection(
-    //     &self,
-    //     db: &mut Database,
-    // ) -> &mut PooledConnection<ConnectionManager<diesel::PgConnection>>;
-    //  {
-    //     //db.connection.as_mut().unwrap()
-    // }
+    fn get_pool(&self) -> Result<PgPool, DatabaseError>;
+
+    fn get_connection(&self) -> Result<PgPooledConnection, DatabaseError> {
+        match self.get_pool()?.get() {
+            Ok(connection) => Ok(connection),
+            Err(err) => {
+                error!("Error getting a connection from the pool!: {}", err);
+                Err(DatabaseError::ConnectFailed)
+            }
+        }
+    }
 }
@@ -16,13 +16,16 @@ use super::helpers::seeds::{SeedModels, SeedProps};
 
 impl Consts {
     pub fn new(environment: Environment) -> Self {
-        let system_config_seed_file_path = match environment {
-            Environment::Production => C_SYSTEM_CONFIG_SEED_FILE_PATH.to_string(),
-            Environment::Development => C_SYSTEM_CONFIG_SEED_FILE_PATH_DEV.to_string(),
-        };
-        let role_group_seed_file_path = match environment {
-            Environment::Production => C_ROLE_GROUP_SEED_FILE_PATH.to_string(),
-            Environment::Development => C_ROLE_GROUP_SEED_FILE_PATH_DEV.to_string(),
+        // Local environments seed from the files checked into the repository
+        let (system_config_seed_file_path, role_group_seed_file_path) = match environment {
+            Environment::Development | Environment::Test => (
+                C_SYSTEM_CONFIG_SEED_FILE_PATH_DEV.to_string(),
+                C_ROLE_GROUP_SEED_FILE_PATH_DEV.to_string(),
+            ),
+            Environment::Staging | Environment::Production => (
+                C_SYSTEM_CONFIG_SEED_FILE_PATH.to_string(),
+                C_ROLE_GROUP_SEED_FILE_PATH.to_string(),
+            ),
         };
 
         Self {
@@ -11,8 +11,16 @@ pub enum DatabaseError {
         "Failed to connect to database! Please check credentials in the .env file and try again!"
     )]
     ConnectFailed,
+    #[error("Database is not connected yet!")]
+    NotConnected,
     #[error("Failed to seed data!")]
     SeedFailed,
+    #[error("Failed to migrate the database!")]
+    MigrationsFailed,
+    #[error("Failed to lock the database for migrations!")]
+    MigrationsLockFailed,
+    #[error("{pending} database migrations are pending! Run them with --migrations run")]
+    MigrationsPending { pending: usize },
     #[error("Failed to select data!")]
     DataSelectFailed,
     #[error("Failed to create data!")]
@@ -31,6 +39,7 @@ pub enum DatabaseError {
 
 #[non_exhaustive]
 #[derive(Debug, Error)]
+#[allow(clippy::enum_variant_names)]
 pub enum SeedDatabaseError {
     #[error("Failed to obtain seed information!")]
     SeedInfoGetFailed,
@@ -9,12 +9,12 @@ use super::errors::DatabaseError;
 
 pub trait HasName {
     fn get_name(&self) -> &String;
-    fn set_name<'a>(&'a mut self, name: &'a String);
+    fn set_name(&mut self, name: &str);
 }
 
 pub trait HasConfig {
     fn get_config(&self) -> &Option<serde_json::Value>;
-    fn get_config_mut<'a>(&'a mut self) -> &'a mut Option<serde_json::Value>;
+    fn get_config_mut(&mut self) -> &mut Option<serde_json::Value>;
     fn set_config<'a>(&'a mut self, config: &'a serde_json::Value);
 }
 
@@ -9,7 +9,7 @@ use super::{
     HasConfig, HasName,
 };
 
-pub fn is_data_secure<Data>(candidates: &mut Vec<Data>, exceptions: &Vec<Data>) -> bool
+pub fn is_data_secure<Data>(candidates: &mut [Data], exceptions: &[Data]) -> bool
 where
     for<'a> Data: Debug + Ord + HasName + HasConfig + Serialize + Deserialize<'a>,
 {
@@ -25,20 +25,20 @@ where
     true
 }
 
-pub fn is_secure<Data>(candidate: &Data, exceptions: &Vec<Data>) -> bool
+pub fn is_secure<Data>(candidate: &Data, exceptions: &[Data]) -> bool
 where
     for<'a> Data: Debug + HasName + HasConfig + Serialize + Deserialize<'a>,
 {
     is_level_ok(candidate, exceptions)
 }
 
-pub fn any_duplicates<Data>(candidates: &mut Vec<Data>) -> bool
+pub fn any_duplicates<Data>(candidates: &mut [Data]) -> bool
 where
     for<'a> Data: Debug + Ord + HasName + HasConfig + Serialize + Deserialize<'a>,
 {
     candidates.sort();
     candidates
-        .into_iter()
+        .iter_mut()
         .dedup_by(|x, y| x.get_name() == y.get_name())
         .collect_vec()
         .len()
@@ -53,7 +53,7 @@ where
     candidates.dedup_by(|a, b| a.eq(&b));
 }
 
-fn is_level_ok<Data>(candidate: &Data, exceptions: &Vec<Data>) -> bool
+fn is_level_ok<Data>(candidate: &Data, exceptions: &[Data]) -> bool
 where
     for<'a> Data: Debug + HasName + HasConfig + Serialize + Deserialize<'a>,
 {
@@ -79,8 +79,7 @@ where
         .iter()
         .find(|&exc| exc.get_name() == candidate.get_name());
 
-    if exception.is_some() {
-        let except = exception.unwrap();
+    if let Some(except) = exception {
         let exception_level = except.get_config().as_ref().unwrap().get("level").unwrap();
         return candidate_level.unwrap().as_u64().unwrap() == exception_level.as_u64().unwrap();
     }
@@ -90,10 +89,10 @@ where
         return false;
     }
 
-    return true;
+    true
 }
 
-pub fn set_data_secure<Data>(candidates: &mut Vec<Data>, exceptions: &Vec<Data>, filter: bool)
+pub fn set_data_secure<Data>(candidates: &mut Vec<Data>, exceptions: &[Data], filter: bool)
 where
     for<'a> Data: Debug + Ord + HasName + HasConfig + Serialize + Deserialize<'a>,
 {
@@ -106,14 +105,14 @@ where
     remove_duplicates(candidates);
 }
 
-pub fn filter_secure_data<Data>(candidates: &mut Vec<Data>, exceptions: &Vec<Data>)
+pub fn filter_secure_data<Data>(candidates: &mut Vec<Data>, exceptions: &[Data])
 where
     for<'a> Data: Debug + HasName + HasConfig + Serialize + Deserialize<'a>,
 {
     candidates.retain(|candidate| is_secure(candidate, exceptions));
 }
 
-pub fn fix_unsecure_data<Data>(candidates: &mut Vec<Data>, exceptions: &Vec<Data>)
+pub fn fix_unsecure_data<Data>(candidates: &mut [Data], exceptions: &[Data])
 where
     for<'a> Data: Debug + HasName + HasConfig + Serialize + Deserialize<'a>,
 {
@@ -122,7 +121,7 @@ where
     });
 }
 
-pub fn set_level_ok<Data>(candidate: &mut Data, exceptions: &Vec<Data>)
+pub fn set_level_ok<Data>(candidate: &mut Data, exceptions: &[Data])
 where
     for<'a> Data: Debug + HasName + HasConfig + Serialize + Deserialize<'a>,
 {
@@ -155,9 +154,8 @@ where
         .iter()
         .find(|&exc| exc.get_name() == candidate.get_name());
 
-    if exception.is_some() {
+    if let Some(exception) = exception {
         let exception_level = exception
-            .unwrap()
             .get_config()
             .as_ref()
             .unwrap()
@@ -165,9 +163,7 @@ where
             .unwrap()
             .as_u64()
             .unwrap();
-        if exception.unwrap().get_name() == candidate.get_name()
-            && candidate_level != exception_level
-        {
+        if exception.get_name() == candidate.get_name() && candidate_level != exception_level {
             protect_exception(candidate);
         }
         return;
@@ -193,7 +189,7 @@ fn protect_exception<Data>(candidate: &mut Data)
 where
     for<'a> Data: Debug + HasName + HasConfig + Serialize + Deserialize<'a>,
 {
-    candidate.set_name(&String::from("TEST"));
+    candidate.set_name("TEST");
     let candidate_config = candidate.get_config_mut().as_mut().unwrap();
 
     upsert_obj_prop(candidate_config, &"level".to_string(), json!(0), true);
@@ -203,7 +199,7 @@ pub fn get_max_level() -> u32 {
     u32::MAX
 }
 
-fn get_max_allowed_level<Data>(group: &Vec<Data>) -> u64
+fn get_max_allowed_level<Data>(group: &[Data]) -> u64
 where
     for<'a> Data: Debug + HasName + HasConfig + Serialize + Deserialize<'a>,
 {
@@ -78,8 +78,8 @@ where
 
     fn seed_file_check(
         path: &String,
-        predefined: &Vec<Seed>,
-        exceptions: &Vec<Seed>,
+        predefined: &[Seed],
+        exceptions: &[Seed],
     ) -> Result<(), SeedDatabaseError> {
         let mut seeds = Self::get_seeds_from_file(path).unwrap_or(vec![]);
 
@@ -120,9 +120,9 @@ where
             .write(true)
             .create(true)
             .truncate(true)
-            .open(&path)
-            .expect(format!("Failed to create the file {}", &path).as_str());
-        match serde_json::to_writer_pretty(file, &serde_json::to_value(&predefined).unwrap()) {
+            .open(path)
+            .unwrap_or_else(|_| panic!("Failed to create the file {}", &path));
+        match serde_json::to_writer_pretty(file, &serde_json::to_value(predefined).unwrap()) {
             Ok(_) => (),
             Err(err) => {
                 error!("{}", err);
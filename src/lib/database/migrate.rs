@@ -0,0 +1,146 @@
+use std::fmt::{self, Display};
+
+use diesel::migration::{Migration, MigrationSource};
+use diesel::pg::{Pg, PgConnection};
+use diesel::sql_types::BigInt;
+use diesel::{sql_query, RunQueryDsl};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use log::{error, info, warn};
+
+use super::errors::DatabaseError;
+
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("src/lib/database/migrations");
+
+pub const MIGRATIONS_MODES: &[&str] = &["auto", "check", "off"];
+
+// Shared by every celestus instance so only one of them migrates at a time
+const MIGRATIONS_LOCK_KEY: i64 = 0x6365_6c65_7374_7573;
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, strum_macros::EnumString, strum_macros::Display,
+)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum MigrationsMode {
+    #[default]
+    Auto,
+    Check,
+    Off,
+}
+
+#[derive(Clone, Debug)]
+pub struct MigrationState {
+    pub name: String,
+    pub applied: bool,
+}
+
+impl Display for MigrationState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let marker = match self.applied {
+            true => "applied",
+            false => "pending",
+        };
+
+        write!(f, "{:8}{}", marker, self.name)
+    }
+}
+
+pub fn list_migrations(
+    connection: &mut PgConnection,
+) -> Result<Vec<MigrationState>, DatabaseError> {
+    let applied = connection.applied_migrations().map_err(|err| {
+        error!("Failed to read the applied migrations: {}", err);
+        DatabaseError::MigrationsFailed
+    })?;
+
+    Ok(get_embedded_migrations()?
+        .iter()
+        .map(|migration| MigrationState {
+            name: migration.name().to_string(),
+            applied: applied.contains(&migration.name().version()),
+        })
+        .collect())
+}
+
+pub fn get_pending_migrations(connection: &mut PgConnection) -> Result<Vec<String>, DatabaseError> {
+    Ok(list_migrations(connection)?
+        .into_iter()
+        .filter(|migration| !migration.applied)
+        .map(|migration| migration.name)
+        .collect())
+}
+
+pub fn run_pending_migrations(connection: &mut PgConnection) -> Result<Vec<String>, DatabaseError> {
+    with_migrations_lock(connection, |connection| {
+        // Another instance may have migrated while this one waited for the lock
+        let pending = get_pending_migrations(connection)?;
+        if pending.is_empty() {
+            return Ok(pending);
+        }
+
+        connection
+            .run_pending_migrations(MIGRATIONS)
+            .map_err(|err| {
+                error!("Failed to run the pending migrations: {}", err);
+                DatabaseError::MigrationsFailed
+            })?;
+        for name in pending.iter() {
+            info!("Applied migration {}", name);
+        }
+
+        Ok(pending)
+    })
+}
+
+pub fn revert_last_migration(connection: &mut PgConnection) -> Result<String, DatabaseError> {
+    with_migrations_lock(connection, |connection| {
+        let reverted = connection
+            .revert_last_migration(MIGRATIONS)
+            .map_err(|err| {
+                error!("Failed to revert the last migration: {}", err);
+                DatabaseError::MigrationsFailed
+            })?;
+        let name = get_embedded_migrations()?
+            .iter()
+            .find(|migration| migration.name().version() == reverted)
+            .map_or_else(
+                || reverted.to_string(),
+                |migration| migration.name().to_string(),
+            );
+        info!("Reverted migration {}", name);
+
+        Ok(name)
+    })
+}
+
+fn get_embedded_migrations() -> Result<Vec<Box<dyn Migration<Pg>>>, DatabaseError> {
+    MIGRATIONS.migrations().map_err(|err| {
+        error!("Failed to read the embedded migrations: {}", err);
+        DatabaseError::MigrationsFailed
+    })
+}
+
+// The advisory lock belongs to the session, so it is taken and released on the same connection
+fn with_migrations_lock<Output>(
+    connection: &mut PgConnection,
+    migrate: impl FnOnce(&mut PgConnection) -> Result<Output, DatabaseError>,
+) -> Result<Output, DatabaseError> {
+    info!("Waiting for the migrations lock...");
+    sql_query("SELECT pg_advisory_lock($1)")
+        .bind::<BigInt, _>(MIGRATIONS_LOCK_KEY)
+        .execute(connection)
+        .map_err(|err| {
+            error!("Failed to take the migrations lock: {}", err);
+            DatabaseError::MigrationsLockFailed
+        })?;
+
+    let result = migrate(connection);
+
+    if let Err(err) = sql_query("SELECT pg_advisory_unlock($1)")
+        .bind::<BigInt, _>(MIGRATIONS_LOCK_KEY)
+        .execute(connection)
+    {
+        warn!("Failed to release the migrations lock: {}", err);
+    }
+
+    result
+}
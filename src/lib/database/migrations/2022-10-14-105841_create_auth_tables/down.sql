@@ -1,8 +1,6 @@
-BEGIN;
 DROP TABLE IF EXISTS public.system_configs;
 DROP TABLE IF EXISTS public.feature_flags;
 DROP TABLE IF EXISTS public.users;
 DROP TABLE IF EXISTS public.roles;
 DROP TABLE IF EXISTS public.user_groups;
 DROP TABLE IF EXISTS public.role_groups;
-END;
\ No newline at end of file
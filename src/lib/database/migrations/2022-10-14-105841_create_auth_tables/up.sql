@@ -1,6 +1,5 @@
 -- This script was generated by a beta version of the ERD tool in pgAdmin 4.
 -- Please log an issue at https://redmine.postgresql.org/projects/pgadmin4/issues/new if you find any bugs, including reproduction steps.
-BEGIN;
 CREATE EXTENSION IF NOT EXISTS pgcrypto;
 CREATE TABLE IF NOT EXISTS public.roles (
     id uuid NOT NULL DEFAULT gen_random_uuid(),
@@ -85,4 +84,3 @@ ALTER TABLE IF EXISTS public.users
 ADD FOREIGN KEY (user_group_id) REFERENCES public.user_groups (id) MATCH SIMPLE ON UPDATE NO ACTION ON DELETE NO ACTION NOT VALID;
 ALTER TABLE IF EXISTS public.users
 ADD FOREIGN KEY (role_id) REFERENCES public.roles (id) MATCH SIMPLE ON UPDATE NO ACTION ON DELETE NO ACTION NOT VALID;
-END;
\ No newline at end of file
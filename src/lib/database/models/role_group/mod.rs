@@ -31,15 +31,15 @@ impl HasName for RoleGroup {
     fn get_name(&self) -> &String {
         &self.name
     }
-    fn set_name(&mut self, name: &String) {
-        self.name = name.clone()
+    fn set_name(&mut self, name: &str) {
+        self.name = name.to_string()
     }
 }
 impl HasConfig for RoleGroup {
-    fn get_config<'a>(&'a self) -> &'a Option<serde_json::Value> {
+    fn get_config(&self) -> &Option<serde_json::Value> {
         &self.config
     }
-    fn get_config_mut<'a>(&'a mut self) -> &'a mut Option<serde_json::Value> {
+    fn get_config_mut(&mut self) -> &mut Option<serde_json::Value> {
         &mut self.config
     }
     fn set_config<'a>(&'a mut self, config: &'a serde_json::Value) {
@@ -109,16 +109,16 @@ impl HasName for RoleGroupInput {
     fn get_name(&self) -> &String {
         &self.name
     }
-    fn set_name<'a>(&'a mut self, name: &'a String) {
-        self.name = name.clone()
+    fn set_name(&mut self, name: &str) {
+        self.name = name.to_string()
     }
 }
 impl HasConfig for RoleGroupInput {
-    fn get_config<'a>(&'a self) -> &'a Option<serde_json::Value> {
+    fn get_config(&self) -> &Option<serde_json::Value> {
         &self.config
     }
 
-    fn get_config_mut<'a>(&'a mut self) -> &'a mut Option<serde_json::Value> {
+    fn get_config_mut(&mut self) -> &mut Option<serde_json::Value> {
         &mut self.config
     }
 
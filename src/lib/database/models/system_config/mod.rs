@@ -1,4 +1,4 @@
-use std::{cmp::Ordering, ops::Deref, time::SystemTime};
+use std::{cmp::Ordering, collections::HashMap, ops::Deref, time::SystemTime};
 
 use diesel::prelude::*;
 use log::error;
@@ -23,20 +23,42 @@ pub struct SystemConfig {
     pub hidden_at: Option<SystemTime>,
 }
 
+impl SystemConfig {
+    pub fn get_settings(
+        connection: &mut PgConnection,
+    ) -> Result<HashMap<String, serde_json::Value>, DatabaseError> {
+        let rows = match system_configs::table
+            .filter(system_configs::deleted_at.is_null())
+            .select((system_configs::name, system_configs::config))
+            .load::<(String, Option<serde_json::Value>)>(connection)
+        {
+            Ok(res) => res,
+            Err(err) => {
+                error!("{}", err);
+                return Err(DatabaseError::DataSelectFailed);
+            }
+        };
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(name, config)| Some((name.to_lowercase(), config?)))
+            .collect())
+    }
+}
 impl HasName for SystemConfig {
     fn get_name(&self) -> &String {
         &self.name
     }
-    fn set_name<'a>(&'a mut self, name: &'a String) {
-        self.name = name.clone()
+    fn set_name(&mut self, name: &str) {
+        self.name = name.to_string()
     }
 }
 impl HasConfig for SystemConfig {
-    fn get_config<'a>(&'a self) -> &'a Option<serde_json::Value> {
+    fn get_config(&self) -> &Option<serde_json::Value> {
         &self.config
     }
 
-    fn get_config_mut<'a>(&'a mut self) -> &'a mut Option<serde_json::Value> {
+    fn get_config_mut(&mut self) -> &mut Option<serde_json::Value> {
         &mut self.config
     }
 
@@ -72,16 +94,16 @@ impl HasName for SystemConfigInput {
     fn get_name(&self) -> &String {
         &self.name
     }
-    fn set_name<'a>(&'a mut self, name: &'a String) {
-        self.name = name.clone()
+    fn set_name(&mut self, name: &str) {
+        self.name = name.to_string()
     }
 }
 impl HasConfig for SystemConfigInput {
-    fn get_config<'a>(&'a self) -> &'a Option<serde_json::Value> {
+    fn get_config(&self) -> &Option<serde_json::Value> {
         &self.config
     }
 
-    fn get_config_mut<'a>(&'a mut self) -> &'a mut Option<serde_json::Value> {
+    fn get_config_mut(&mut self) -> &mut Option<serde_json::Value> {
         &mut self.config
     }
 
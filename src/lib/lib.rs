@@ -1,4 +1,4 @@
 pub mod cache;
-// pub mod database;
+pub mod database;
 pub mod providers;
 pub mod utils;
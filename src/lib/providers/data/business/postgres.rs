@@ -1,15 +1,68 @@
-use serde::{Deserialize, Serialize};
+use getset::Getters;
+use serde::{Deserialize, Deserializer, Serialize};
 
-#[derive(Debug, Deserialize, Serialize)]
-#[allow(dead_code)]
+use crate::providers::secrets::secret::SecretString;
+
+pub const POSTGRES_SECRETS_PATH: &str = "database/pg";
+
+#[derive(Debug, Deserialize, Serialize, Getters)]
+#[getset(get = "pub with_prefix")]
 pub struct PostgresData {
-    pg_database_name: String,
-    pg_host: String,
-    pg_password: String,
-    pg_port: i32,
-    pg_url: String,
-    pg_url_prefix: String,
-    pg_user: String,
+    pub(crate) pg_database_name: String,
+    pub(crate) pg_host: String,
+    pub(crate) pg_password: SecretString,
+    #[serde(deserialize_with = "deserialize_port")]
+    pub(crate) pg_port: i32,
+    pub(crate) pg_url: SecretString,
+    pub(crate) pg_url_prefix: String,
+    pub(crate) pg_user: String,
+}
+
+#[derive(Debug, Deserialize, Getters)]
+#[getset(get = "pub with_prefix")]
+pub struct PostgresCredentials {
+    username: String,
+    password: SecretString,
+    #[serde(default)]
+    lease_id: String,
+    #[serde(default)]
+    lease_duration: u32,
+    #[serde(default)]
+    renewable: bool,
+}
+
+impl PostgresData {
+    pub fn apply_credentials(&mut self, credentials: PostgresCredentials) {
+        self.pg_user = credentials.username;
+        self.pg_password = credentials.password;
+        self.pg_url = self.to_url();
+    }
+
+    pub fn to_url(&self) -> SecretString {
+        SecretString::new(format!(
+            "{}://{}:{}@{}:{}/{}",
+            self.pg_url_prefix,
+            self.pg_user,
+            self.pg_password.expose_secret(),
+            self.pg_host,
+            self.pg_port,
+            self.pg_database_name
+        ))
+    }
+}
+
+fn deserialize_port<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Port {
+        Number(i32),
+        Text(String),
+    }
+
+    match Port::deserialize(deserializer)? {
+        Port::Number(port) => Ok(port),
+        Port::Text(port) => port.trim().parse().map_err(serde::de::Error::custom),
+    }
 }
 
 pub struct Postgres {
@@ -11,6 +11,15 @@ pub enum DataProvision {
     OnDemand,
 }
 
+impl DataProvision {
+    pub fn from_single_use(single_use: Option<bool>) -> Self {
+        match single_use {
+            Some(false) => DataProvision::OnDemand,
+            _ => DataProvision::OneTime,
+        }
+    }
+}
+
 #[derive(PartialEq)]
 pub enum DataProviderConnectivity {
     SingleConnection,
@@ -0,0 +1,113 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::errors::SecretsError;
+use super::file::load_file_secrets_provider;
+use super::mounted::load_mounted_secrets_provider;
+use super::vault::load_vault_secrets_provider;
+use super::SecretsProvider;
+
+pub const ENV_SECRETS_PROVIDER_KIND_SUFFIX: &str = "KIND";
+
+#[derive(Clone, Copy, Debug, PartialEq, strum_macros::EnumString, strum_macros::Display)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum SecretsProviderKind {
+    Vault,
+    File,
+    Mounted,
+}
+
+impl SecretsProviderKind {
+    pub async fn load_provider(&self, provider_name: &str) -> Result<SecretsProvider> {
+        match self {
+            SecretsProviderKind::Vault => load_vault_secrets_provider(provider_name).await,
+            SecretsProviderKind::File => load_file_secrets_provider(provider_name),
+            SecretsProviderKind::Mounted => load_mounted_secrets_provider(provider_name),
+        }
+    }
+}
+
+#[async_trait]
+pub trait SecretsBackend: Send + Sync {
+    fn get_kind(&self) -> SecretsProviderKind;
+
+    async fn read_secret(&self, path: &str) -> Result<serde_json::Value>;
+
+    async fn list_keys(&self, path: &str) -> Result<Vec<String>>;
+
+    async fn login(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn renew(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn get_renewal_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    // Every read hands out new credentials under a lease, so the reader has to keep it
+    fn issues_leases(&self) -> bool {
+        false
+    }
+
+    async fn renew_lease(&self, _lease_id: &str, _increment: Duration) -> Result<Duration> {
+        Err(SecretsError::Unsupported(format!(
+            "{} Secrets Provider does not issue leases",
+            self.get_kind()
+        ))
+        .into())
+    }
+
+    async fn revoke_lease(&self, _lease_id: &str) -> Result<()> {
+        Err(SecretsError::Unsupported(format!(
+            "{} Secrets Provider does not issue leases",
+            self.get_kind()
+        ))
+        .into())
+    }
+
+    async fn health(&self) -> Result<()>;
+
+    async fn get_version(&self, path: &str) -> Result<Option<String>> {
+        Ok(Some(fingerprint(&self.read_secret(path).await?)))
+    }
+
+    async fn encrypt(&self, _key: &str, _plaintext: &[u8]) -> Result<String> {
+        Err(SecretsError::Unsupported(format!(
+            "{} Secrets Provider does not support encryption",
+            self.get_kind()
+        ))
+        .into())
+    }
+
+    async fn decrypt(&self, _key: &str, _ciphertext: &str) -> Result<Vec<u8>> {
+        Err(SecretsError::Unsupported(format!(
+            "{} Secrets Provider does not support decryption",
+            self.get_kind()
+        ))
+        .into())
+    }
+}
+
+pub fn fingerprint(value: &serde_json::Value) -> String {
+    let mut hasher = DefaultHasher::new();
+    value.to_string().hash(&mut hasher);
+
+    format!("{:016x}", hasher.finish())
+}
+
+impl dyn SecretsBackend {
+    pub async fn read<DataStruct: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+    ) -> Result<DataStruct> {
+        Ok(serde_json::from_value(self.read_secret(path).await?)?)
+    }
+}
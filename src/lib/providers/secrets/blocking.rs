@@ -0,0 +1,70 @@
+use anyhow::Result;
+use crossbeam::channel::Receiver;
+use serde::Deserialize;
+
+use crate::providers::data::business::postgres::PostgresData;
+use crate::utils::runtime::block_on;
+
+use super::errors::SecretsError;
+use super::watch::SecretChange;
+use super::SecretsProviders;
+
+pub struct BlockingSecretsProviders<'a> {
+    providers: &'a SecretsProviders,
+}
+
+impl<'a> BlockingSecretsProviders<'a> {
+    pub(super) fn new(providers: &'a SecretsProviders) -> Self {
+        Self { providers }
+    }
+
+    pub fn get_postgres_data(&self) -> Result<PostgresData> {
+        block_on(self.providers.get_postgres_data())
+    }
+
+    pub fn revoke_leases(&self) {
+        block_on(self.providers.revoke_leases())
+    }
+
+    pub fn get<DataStruct: for<'de> Deserialize<'de> + Send>(
+        &self,
+        provider_name: &str,
+        path: &str,
+    ) -> Result<DataStruct, SecretsError> {
+        block_on(self.providers.get::<DataStruct>(provider_name, path))
+    }
+
+    pub fn list(&self, provider_name: &str, path: &str) -> Result<Vec<String>, SecretsError> {
+        block_on(self.providers.list(provider_name, path))
+    }
+
+    pub fn subscribe(
+        &self,
+        provider_name: &str,
+        path: &str,
+    ) -> Result<Receiver<SecretChange>, SecretsError> {
+        block_on(self.providers.subscribe(provider_name, path))
+    }
+
+    pub fn subscribe_any(&self, path: &str) -> Result<Receiver<SecretChange>, SecretsError> {
+        block_on(self.providers.subscribe_any(path))
+    }
+
+    pub fn encrypt(
+        &self,
+        provider_name: &str,
+        key: &str,
+        plaintext: &[u8],
+    ) -> Result<String, SecretsError> {
+        block_on(self.providers.encrypt(provider_name, key, plaintext))
+    }
+
+    pub fn decrypt(
+        &self,
+        provider_name: &str,
+        key: &str,
+        ciphertext: &str,
+    ) -> Result<Vec<u8>, SecretsError> {
+        block_on(self.providers.decrypt(provider_name, key, ciphertext))
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::providers::{DataProviderName, DataProvision};
+
+use super::secret::SecretString;
+
+pub const ENV_SECRETS_CACHE_TTL: &str = "SECRETS_CACHE_TTL";
+
+type CacheKey = (DataProviderName, String);
+
+struct CachedSecret {
+    value: SecretString,
+    fetched_at: Instant,
+}
+
+pub struct SecretsCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<CacheKey, CachedSecret>>,
+}
+
+impl SecretsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get_ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn get(
+        &self,
+        provider_name: &DataProviderName,
+        path: &str,
+        provision_type: &DataProvision,
+    ) -> Option<SecretString> {
+        let mut entries = self.entries.lock().ok()?;
+        let key = (provider_name.clone(), path.to_owned());
+
+        let cached = entries.get(&key)?;
+        if self.is_fresh(cached, provision_type) {
+            return Some(cached.value.clone());
+        }
+
+        entries.remove(&key);
+        None
+    }
+
+    pub fn insert(
+        &self,
+        provider_name: &DataProviderName,
+        path: &str,
+        provision_type: &DataProvision,
+        value: SecretString,
+    ) {
+        if *provision_type == DataProvision::OnDemand && self.ttl.is_zero() {
+            return;
+        }
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+
+        entries.insert(
+            (provider_name.clone(), path.to_owned()),
+            CachedSecret {
+                value,
+                fetched_at: Instant::now(),
+            },
+        );
+    }
+
+    pub fn invalidate(&self, provider_name: &DataProviderName, path: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.remove(&(provider_name.clone(), path.to_owned()));
+        }
+    }
+
+    pub fn invalidate_provider(&self, provider_name: &DataProviderName) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.retain(|(name, _), _| name != provider_name);
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    fn is_fresh(&self, cached: &CachedSecret, provision_type: &DataProvision) -> bool {
+        match provision_type {
+            DataProvision::OneTime => true,
+            DataProvision::OnDemand => cached.fetched_at.elapsed() < self.ttl,
+        }
+    }
+}
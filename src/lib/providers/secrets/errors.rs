@@ -0,0 +1,71 @@
+use std::io;
+
+use ::thiserror::Error;
+use vaultrs::error::ClientError;
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SecretsError {
+    #[error("Secrets Provider \"{0}\" is not configured!")]
+    ProviderNotFound(String),
+    #[error("Secret not found: {0}")]
+    NotFound(String),
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+    #[error("Secrets Provider is sealed: {0}")]
+    Sealed(String),
+    #[error("Failed to reach Secrets Provider: {0}")]
+    Network(String),
+    #[error("Failed to deserialize secret: {0}")]
+    Deserialize(String),
+    #[error("Operation is not supported: {0}")]
+    Unsupported(String),
+    #[error("Secrets Provider failed: {0}")]
+    Other(String),
+}
+
+impl SecretsError {
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, SecretsError::NotFound(_))
+    }
+}
+
+impl From<anyhow::Error> for SecretsError {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<SecretsError>() {
+            Ok(secrets_error) => return secrets_error,
+            Err(err) => err,
+        };
+
+        let message = format!("{:#}", err);
+
+        if let Some(client_error) = err.downcast_ref::<ClientError>() {
+            return match client_error {
+                ClientError::APIError { code: 404, .. } => SecretsError::NotFound(message),
+                ClientError::APIError {
+                    code: 401 | 403, ..
+                } => SecretsError::PermissionDenied(message),
+                ClientError::APIError { code: 503, .. } => SecretsError::Sealed(message),
+                ClientError::RestClientError { .. } | ClientError::RestClientBuildError { .. } => {
+                    SecretsError::Network(message)
+                }
+                ClientError::JsonParseError { .. } => SecretsError::Deserialize(message),
+                _ => SecretsError::Other(message),
+            };
+        }
+
+        if let Some(io_error) = err.downcast_ref::<io::Error>() {
+            return match io_error.kind() {
+                io::ErrorKind::NotFound => SecretsError::NotFound(message),
+                io::ErrorKind::PermissionDenied => SecretsError::PermissionDenied(message),
+                _ => SecretsError::Other(message),
+            };
+        }
+
+        if err.downcast_ref::<serde_json::Error>().is_some() {
+            return SecretsError::Deserialize(message);
+        }
+
+        SecretsError::Other(message)
+    }
+}
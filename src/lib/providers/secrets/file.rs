@@ -0,0 +1,291 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use getset::Getters;
+use serde_derive::Deserialize;
+
+use crate::providers::{
+    DataProvider, DataProviderConnectivity, DataProvision, DataProvisionActions,
+};
+
+use super::backend::{SecretsBackend, SecretsProviderKind};
+use super::errors::SecretsError;
+use super::{get_default_base_path, load_provider_from_env, read_structured_file, SecretsProvider};
+
+const SUPPORTED_EXTENSIONS: [&str; 2] = ["json", "toml"];
+
+#[derive(Deserialize, Getters)]
+#[getset(get = "pub with_prefix")]
+pub struct FileEnvData {
+    path: String,
+    base_path: Option<String>,
+    single_use: Option<bool>,
+}
+
+impl DataProvisionActions for FileEnvData {
+    fn get_provision_type(&self) -> DataProvision {
+        DataProvision::from_single_use(self.single_use)
+    }
+}
+
+#[derive(Getters)]
+#[getset(get = "pub with_prefix")]
+pub struct FileSecrets {
+    root: PathBuf,
+    base_path: String,
+}
+
+impl FileSecrets {
+    pub fn new(provider_info: &FileEnvData) -> Result<Self> {
+        let root = PathBuf::from(&provider_info.path);
+        if !root.exists() {
+            return Err(anyhow!(
+                "Secrets file or directory {} does not exist",
+                root.display()
+            ));
+        }
+
+        let base_path = provider_info
+            .base_path
+            .clone()
+            .unwrap_or_else(get_default_base_path);
+
+        Ok(Self { root, base_path })
+    }
+
+    fn full_path(&self, path: &str) -> String {
+        format!("{}{}", self.base_path, path)
+            .trim_matches('/')
+            .to_string()
+    }
+
+    fn read_from_directory(&self, full_path: &str) -> Result<serde_json::Value> {
+        for extension in SUPPORTED_EXTENSIONS {
+            let candidate = self.root.join(format!("{}.{}", full_path, extension));
+            if candidate.is_file() {
+                return read_structured_file(&candidate);
+            }
+        }
+
+        let candidate = self.root.join(full_path);
+        if candidate.is_file() {
+            return read_structured_file(&candidate);
+        }
+
+        Err(SecretsError::NotFound(format!("\"{}\" in {}", full_path, self.root.display())).into())
+    }
+
+    fn read_from_file(&self, full_path: &str) -> Result<serde_json::Value> {
+        let contents = read_structured_file(&self.root)?;
+
+        if let Some(value) = contents.get(full_path) {
+            return Ok(value.clone());
+        }
+
+        full_path
+            .split('/')
+            .try_fold(&contents, |node, segment| node.get(segment))
+            .cloned()
+            .ok_or_else(|| {
+                SecretsError::NotFound(format!("\"{}\" in {}", full_path, self.root.display()))
+                    .into()
+            })
+    }
+}
+
+#[async_trait]
+impl SecretsBackend for FileSecrets {
+    fn get_kind(&self) -> SecretsProviderKind {
+        SecretsProviderKind::File
+    }
+
+    async fn read_secret(&self, path: &str) -> Result<serde_json::Value> {
+        let full_path = self.full_path(path);
+
+        if self.root.is_dir() {
+            self.read_from_directory(&full_path)
+        } else {
+            self.read_from_file(&full_path)
+        }
+    }
+
+    async fn list_keys(&self, path: &str) -> Result<Vec<String>> {
+        let full_path = self.full_path(path);
+
+        if self.root.is_file() {
+            let node = self.read_from_file(&full_path)?;
+            let object = node
+                .as_object()
+                .ok_or_else(|| anyhow!("\"{}\" is not a folder of secrets", full_path))?;
+
+            return Ok(object.keys().cloned().collect());
+        }
+
+        let mut keys = Vec::new();
+        for entry in fs::read_dir(self.root.join(&full_path))? {
+            let entry_path = entry?.path();
+            let Some(name) = entry_path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            if entry_path.is_dir() {
+                keys.push(format!("{}/", name));
+            } else {
+                keys.push(name.to_string());
+            }
+        }
+        keys.sort();
+
+        Ok(keys)
+    }
+
+    async fn health(&self) -> Result<()> {
+        if self.root.exists() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Secrets file or directory {} is gone",
+                self.root.display()
+            ))
+        }
+    }
+}
+
+pub(super) fn load_file_secrets_provider(provider_name: &str) -> Result<SecretsProvider> {
+    let parsed_env_data: FileEnvData = load_provider_from_env::<FileEnvData>(provider_name)?;
+
+    let provision_type = parsed_env_data.get_provision_type();
+
+    let implementation = FileSecrets::new(&parsed_env_data)?;
+
+    Ok(DataProvider {
+        name: provider_name.to_lowercase(),
+        prefix: format!("{}_", provider_name.to_lowercase()),
+        connection_info: None,
+        provision_type,
+        connectivity: DataProviderConnectivity::SingleConnection,
+        implementation: Some(Arc::new(implementation)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn file_secrets(root: &Path) -> FileSecrets {
+        FileSecrets::new(&FileEnvData {
+            path: root.display().to_string(),
+            base_path: Some("test/".to_string()),
+            single_use: None,
+        })
+        .unwrap()
+    }
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[tokio::test]
+    async fn reads_kv_paths_from_a_directory() {
+        let root = TempDir::new().unwrap();
+        write(
+            root.path(),
+            "test/database/pg.json",
+            r#"{"username": "celestus", "password": "secret"}"#,
+        );
+        write(
+            root.path(),
+            "test/database/redis.toml",
+            "username = \"celestus\"\nport = 6379\n",
+        );
+        let secrets = file_secrets(root.path());
+
+        assert_eq!(
+            secrets.read_secret("database/pg").await.unwrap(),
+            json!({"username": "celestus", "password": "secret"})
+        );
+        assert_eq!(
+            secrets.read_secret("database/redis").await.unwrap(),
+            json!({"username": "celestus", "port": 6379})
+        );
+        assert_eq!(
+            secrets.list_keys("database").await.unwrap(),
+            vec!["pg".to_string(), "redis".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn reads_kv_paths_from_a_json_file() {
+        let root = TempDir::new().unwrap();
+        write(
+            root.path(),
+            "secrets.json",
+            r#"{"test": {"database": {"pg": {"username": "celestus"}}}, "test/flat": 1}"#,
+        );
+        let secrets = file_secrets(&root.path().join("secrets.json"));
+
+        assert_eq!(
+            secrets.read_secret("database/pg").await.unwrap(),
+            json!({"username": "celestus"})
+        );
+        assert_eq!(secrets.read_secret("flat").await.unwrap(), json!(1));
+        assert_eq!(
+            secrets.list_keys("database").await.unwrap(),
+            vec!["pg".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn reads_kv_paths_from_a_toml_file() {
+        let root = TempDir::new().unwrap();
+        write(
+            root.path(),
+            "secrets.toml",
+            "[test.database.pg]\nusername = \"celestus\"\nport = 5432\n",
+        );
+        let secrets = file_secrets(&root.path().join("secrets.toml"));
+
+        assert_eq!(
+            secrets.read_secret("database/pg").await.unwrap(),
+            json!({"username": "celestus", "port": 5432})
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_paths_are_not_found() {
+        let root = TempDir::new().unwrap();
+        write(root.path(), "secrets.json", r#"{"test": {"database": {}}}"#);
+        let directory = file_secrets(root.path());
+        let file = file_secrets(&root.path().join("secrets.json"));
+
+        for secrets in [directory, file] {
+            let err = secrets.read_secret("database/pg").await.unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<SecretsError>(),
+                Some(SecretsError::NotFound(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn missing_roots_are_rejected() {
+        let root = TempDir::new().unwrap();
+
+        assert!(FileSecrets::new(&FileEnvData {
+            path: root.path().join("missing").display().to_string(),
+            base_path: None,
+            single_use: None,
+        })
+        .is_err());
+    }
+}
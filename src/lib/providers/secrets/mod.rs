@@ -1,125 +1,554 @@
-mod vault;
+pub mod backend;
+pub mod blocking;
+pub mod cache;
+pub mod errors;
+pub mod file;
+pub mod mounted;
+pub mod renewal;
+pub mod secret;
+pub mod vault;
+pub mod watch;
 
 use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 
+use anyhow::{anyhow, Context, Result};
+use crossbeam::channel::{unbounded, Receiver};
 use getset::Getters;
-use log::warn;
+use log::{debug, info, warn};
 use serde::Deserialize;
 
-use crate::utils::web::{URLData, URLInfo};
+use crate::providers::data::business::postgres::{
+    PostgresCredentials, PostgresData, POSTGRES_SECRETS_PATH,
+};
+use crate::utils::environment::get_environment;
+use crate::utils::errors::{ConfigError, ConfigReport};
+use crate::utils::runtime::block_on;
+use crate::utils::web::URLData;
 
-use self::vault::{Vault, VaultEnvData, VaultSecretsEngine, VAULT_SECRETS_PROVIDER_NAME};
+use self::backend::{SecretsBackend, SecretsProviderKind, ENV_SECRETS_PROVIDER_KIND_SUFFIX};
+use self::blocking::BlockingSecretsProviders;
+use self::cache::SecretsCache;
+use self::errors::SecretsError;
+use self::renewal::{SecretLease, SecretsRenewal};
+use self::secret::SecretString;
+use self::watch::{SecretChange, SecretsWatcher};
 
-use super::{DataProvider, DataProviderConnectivity, DataProviderName, DataProvisionActions};
+use super::{
+    DataProvider, DataProviderConnectivity, DataProviderName, DataProvision, DataProvisionActions,
+};
 
-pub const SETTING_USE_SECRETS_PROVIDER: &str = "use_secrets_provider";
+pub const SETTING_USE_SECRETS_PROVIDER: &str = "secrets.enabled";
 pub const ENV_USE_SECRETS_PROVIDER: &str = "USE_SECRETS_PROVIDER";
 
-pub const SETTING_SECRETS_PROVIDERS: &str = "secrets_providers";
+pub const SETTING_SECRETS_PROVIDERS: &str = "secrets.providers";
 pub const ENV_SECRETS_PROVIDERS: &str = "SECRETS_PROVIDERS";
 
-pub type SecretsProvider = DataProvider<URLData, SecretsProviderImplementation>;
+pub type SecretsProvider = DataProvider<Option<URLData>, SecretsProviderImplementation>;
 
-#[derive(Default, Getters)]
+pub type SecretsProviderImplementation = Arc<dyn SecretsBackend>;
+
+#[derive(Getters)]
 #[getset(get = "pub with_prefix")]
 pub struct SecretsProviders {
     providers: HashMap<DataProviderName, SecretsProvider>,
+    #[getset(skip)]
+    renewal: SecretsRenewal,
+    #[getset(skip)]
+    cache: Arc<SecretsCache>,
+    #[getset(skip)]
+    watcher: Option<SecretsWatcher>,
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "lowercase")]
-struct SecretsProvidersEnvVar {
-    secrets_providers: Option<Vec<String>>,
-}
-
-pub enum SecretsProviderImplementation {
-    Vault(Vault),
+// Resolved from the settings, see SettingsCache::get_secrets_options
+#[derive(Clone, Debug)]
+pub struct SecretsOptions {
+    pub providers: Vec<String>,
+    pub cache_ttl: Duration,
+    pub watch_interval: Duration,
 }
 
 impl SecretsProviders {
-    pub fn new() -> Self {
-        let secrets_providers_names = load_secrets_providers_names();
+    pub fn new(options: &SecretsOptions) -> Result<Self, ConfigReport> {
+        block_on(Self::load(options))
+    }
 
-        let found_secrets_providers = load_secrets_providers(&secrets_providers_names);
+    pub async fn load(options: &SecretsOptions) -> Result<Self, ConfigReport> {
+        if options.providers.is_empty() {
+            return Err(ConfigError::MissingVariable {
+                name: ENV_SECRETS_PROVIDERS.to_owned(),
+            }
+            .into());
+        }
 
-        let mut providers = HashMap::<DataProviderName, SecretsProvider>::new();
+        let found_secrets_providers = load_secrets_providers(&options.providers).await?;
 
+        let mut created = Self::start(options);
         for found_providers in found_secrets_providers.into_iter() {
-            providers.insert(found_providers.get_name().into(), found_providers);
+            created
+                .providers
+                .insert(found_providers.get_name().into(), found_providers);
         }
+        created.restart_renewal();
 
-        Self { providers }
+        Ok(created)
+    }
+
+    // Starts the cache and watcher without any provider, they are added with register
+    pub fn start(options: &SecretsOptions) -> Self {
+        let cache = Arc::new(SecretsCache::new(options.cache_ttl));
+        let watcher = Some(SecretsWatcher::start(cache.clone(), options.watch_interval));
+
+        let providers = HashMap::new();
+
+        Self {
+            renewal: SecretsRenewal::start(&providers),
+            providers,
+            cache,
+            watcher,
+        }
+    }
+
+    pub fn blocking(&self) -> BlockingSecretsProviders<'_> {
+        BlockingSecretsProviders::new(self)
+    }
+
+    pub fn restart_renewal(&mut self) {
+        self.renewal.restart(&self.providers);
+    }
+
+    pub async fn revoke_leases(&self) {
+        self.renewal.revoke_leases().await;
+    }
+
+    pub fn register(
+        &mut self,
+        provider_name: &str,
+        provision_type: DataProvision,
+        implementation: SecretsProviderImplementation,
+    ) {
+        let name = provider_name.to_lowercase();
+
+        self.cache.invalidate_provider(&name.as_str().into());
+        self.providers.insert(
+            name.as_str().into(),
+            DataProvider {
+                prefix: format!("{}_", name),
+                name,
+                connection_info: None,
+                provision_type,
+                connectivity: DataProviderConnectivity::SingleConnection,
+                implementation: Some(implementation),
+            },
+        );
+
+        self.restart_renewal();
+    }
+
+    pub async fn get_postgres_data(&self) -> Result<PostgresData> {
+        let mut data = self
+            .get_from_any::<PostgresData>(POSTGRES_SECRETS_PATH)
+            .await?;
+
+        if let Some(credentials) = self.lease_postgres_credentials().await {
+            data.apply_credentials(credentials);
+        }
+
+        Ok(data)
+    }
+
+    // Every read mints a new database user, so there is exactly one read per connect and its
+    // lease is kept until new credentials replace it
+    async fn lease_postgres_credentials(&self) -> Option<PostgresCredentials> {
+        for (name, provider) in self.get_sorted_providers(true) {
+            let Some(backend) = provider.get_implementation() else {
+                continue;
+            };
+
+            let credentials = match backend
+                .read::<PostgresCredentials>(POSTGRES_SECRETS_PATH)
+                .await
+            {
+                Ok(credentials) => credentials,
+                Err(err) => {
+                    warn!(
+                        "Secrets Provider {} could not provide \"{}\": {}",
+                        provider.get_name(),
+                        POSTGRES_SECRETS_PATH,
+                        err
+                    );
+                    continue;
+                }
+            };
+
+            info!(
+                "Using dynamic database credentials, lease {} valid for {}s",
+                credentials.get_lease_id(),
+                credentials.get_lease_duration()
+            );
+            self.renewal.keep_lease(
+                name.clone(),
+                POSTGRES_SECRETS_PATH,
+                backend.clone(),
+                SecretLease {
+                    lease_id: credentials.get_lease_id().clone(),
+                    duration: Duration::from_secs(u64::from(*credentials.get_lease_duration())),
+                    renewable: *credentials.get_renewable(),
+                },
+                self.watcher.as_ref().map(SecretsWatcher::get_notifier),
+            );
+
+            return Some(credentials);
+        }
+
+        None
+    }
+
+    // Providers issuing leases are left out of plain reads, each read there is a new lease
+    fn get_sorted_providers(&self, leased: bool) -> Vec<(&DataProviderName, &SecretsProvider)> {
+        let mut providers: Vec<(&DataProviderName, &SecretsProvider)> = self
+            .providers
+            .iter()
+            .filter(|(_, provider)| {
+                provider
+                    .get_implementation()
+                    .is_some_and(|backend| backend.issues_leases() == leased)
+            })
+            .collect();
+        providers.sort_by_key(|(name, _)| *name);
+
+        providers
+    }
+
+    pub async fn get<DataStruct: for<'de> Deserialize<'de>>(
+        &self,
+        provider_name: &str,
+        path: &str,
+    ) -> Result<DataStruct, SecretsError> {
+        let provider = self
+            .providers
+            .get(&DataProviderName::from(provider_name.to_lowercase()))
+            .ok_or_else(|| SecretsError::ProviderNotFound(provider_name.to_owned()))?;
+
+        // A cached read would hand out a lease nobody renews or revokes
+        if provider
+            .get_implementation()
+            .is_some_and(|backend| backend.issues_leases())
+        {
+            return Err(SecretsError::Unsupported(format!(
+                "Secrets Provider {} issues leased credentials, use get_postgres_data",
+                provider.get_name()
+            )));
+        }
+
+        let value = self.read_cached(provider, path).await?;
+
+        serde_json::from_str(value.expose_secret())
+            .map_err(|err| SecretsError::Deserialize(format!("\"{}\": {}", path, err)))
+    }
+
+    pub fn invalidate(&self, provider_name: &str, path: &str) {
+        self.cache
+            .invalidate(&DataProviderName::from(provider_name.to_lowercase()), path);
+    }
+
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+    }
+
+    pub async fn subscribe(
+        &self,
+        provider_name: &str,
+        path: &str,
+    ) -> Result<Receiver<SecretChange>, SecretsError> {
+        let watcher = self.get_watcher()?;
+        let name = DataProviderName::from(provider_name.to_lowercase());
+        let backend = self.get_backend(provider_name)?.clone();
+        let (sender, receiver) = unbounded();
+
+        watcher.subscribe(name, path, backend, sender).await?;
+
+        Ok(receiver)
+    }
+
+    pub async fn subscribe_any(&self, path: &str) -> Result<Receiver<SecretChange>, SecretsError> {
+        let watcher = self.get_watcher()?;
+        let (sender, receiver) = unbounded();
+        let mut subscribed = false;
+
+        for (name, provider) in self.providers.iter() {
+            let Some(backend) = provider.get_implementation() else {
+                continue;
+            };
+
+            match watcher
+                .subscribe(name.clone(), path, backend.clone(), sender.clone())
+                .await
+            {
+                Ok(_) => subscribed = true,
+                Err(err) => debug!(
+                    "Secrets Provider {} cannot watch \"{}\": {}",
+                    provider.get_name(),
+                    path,
+                    err
+                ),
+            }
+        }
+
+        if !subscribed {
+            return Err(SecretsError::NotFound(format!(
+                "none of the Secrets Providers can watch \"{}\"",
+                path
+            )));
+        }
+
+        Ok(receiver)
     }
-}
 
-fn load_secrets_providers_names() -> Vec<String> {
-    match envy::from_env::<SecretsProvidersEnvVar>() {
-        Ok(config) => config.secrets_providers.unwrap(),
-        Err(_) => {
-            panic!(
-                "{} is set to TRUE, but environment variable {} is not found",
-                ENV_USE_SECRETS_PROVIDER, ENV_SECRETS_PROVIDERS
+    pub async fn list(&self, provider_name: &str, path: &str) -> Result<Vec<String>, SecretsError> {
+        Ok(self.get_backend(provider_name)?.list_keys(path).await?)
+    }
+
+    pub async fn encrypt(
+        &self,
+        provider_name: &str,
+        key: &str,
+        plaintext: &[u8],
+    ) -> Result<String, SecretsError> {
+        Ok(self
+            .get_backend(provider_name)?
+            .encrypt(key, plaintext)
+            .await?)
+    }
+
+    pub async fn decrypt(
+        &self,
+        provider_name: &str,
+        key: &str,
+        ciphertext: &str,
+    ) -> Result<Vec<u8>, SecretsError> {
+        Ok(self
+            .get_backend(provider_name)?
+            .decrypt(key, ciphertext)
+            .await?)
+    }
+
+    fn get_watcher(&self) -> Result<&SecretsWatcher, SecretsError> {
+        self.watcher
+            .as_ref()
+            .ok_or_else(|| SecretsError::Unsupported("secrets watching is not started".to_owned()))
+    }
+
+    fn get_backend(
+        &self,
+        provider_name: &str,
+    ) -> Result<&SecretsProviderImplementation, SecretsError> {
+        self.providers
+            .get(&DataProviderName::from(provider_name.to_lowercase()))
+            .and_then(|provider| provider.get_implementation())
+            .ok_or_else(|| SecretsError::ProviderNotFound(provider_name.to_owned()))
+    }
+
+    async fn read_cached(&self, provider: &SecretsProvider, path: &str) -> Result<SecretString> {
+        let name = DataProviderName::from(provider.get_name());
+
+        if let Some(cached) = self.cache.get(&name, path, provider.get_provision_type()) {
+            return Ok(cached);
+        }
+
+        let backend = provider.get_implementation().ok_or_else(|| {
+            anyhow!(
+                "Secrets Provider {} has no implementation",
+                provider.get_name()
             )
+        })?;
+
+        let value = SecretString::new(serde_json::to_string(&backend.read_secret(path).await?)?);
+        self.cache
+            .insert(&name, path, provider.get_provision_type(), value.clone());
+
+        Ok(value)
+    }
+
+    async fn read_from<DataStruct: for<'de> Deserialize<'de>>(
+        &self,
+        provider: &SecretsProvider,
+        path: &str,
+    ) -> Result<DataStruct> {
+        let value = self.read_cached(provider, path).await?;
+
+        Ok(serde_json::from_str(value.expose_secret())?)
+    }
+
+    pub async fn find_in_any<DataStruct: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+    ) -> Option<DataStruct> {
+        for (_, provider) in self.get_sorted_providers(false) {
+            match self.read_from::<DataStruct>(provider, path).await {
+                Ok(data) => return Some(data),
+                Err(err) => debug!(
+                    "Secrets Provider {} skipped \"{}\": {}",
+                    provider.get_name(),
+                    path,
+                    err
+                ),
+            }
+        }
+
+        None
+    }
+
+    async fn get_from_any<DataStruct: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+    ) -> Result<DataStruct> {
+        for (_, provider) in self.get_sorted_providers(false) {
+            match self.read_from::<DataStruct>(provider, path).await {
+                Ok(data) => return Ok(data),
+                Err(err) => warn!(
+                    "Secrets Provider {} could not provide \"{}\": {}",
+                    provider.get_name(),
+                    path,
+                    err
+                ),
+            }
         }
+
+        Err(anyhow!(
+            "None of the configured Secrets Providers could provide \"{}\"",
+            path
+        ))
     }
 }
 
-fn load_secrets_providers(providers_names: &[String]) -> Vec<SecretsProvider> {
+async fn load_secrets_providers(
+    providers_names: &[String],
+) -> Result<Vec<SecretsProvider>, ConfigReport> {
     let mut read: Vec<SecretsProvider> = Vec::new();
+    let mut report = ConfigReport::default();
 
     for provider_name in providers_names.iter() {
         let uppercase_name = provider_name.to_uppercase();
+        let kind_var = format!("{}_{}", uppercase_name, ENV_SECRETS_PROVIDER_KIND_SUFFIX);
 
-        if uppercase_name.contains(VAULT_SECRETS_PROVIDER_NAME) {
-            read.extend(load_vault_secrets_provider(uppercase_name));
-        } else {
-            warn!(
-                "{} is not referencing any currently supported Secrets Providers.\n
-            Name must contain currently supported Secrets Providers \n
-            Currently supported: Vault",
-                provider_name
-            )
+        let Ok(kind_value) = env::var(&kind_var) else {
+            report.push(ConfigError::MissingVariable { name: kind_var });
+            continue;
+        };
+
+        let Ok(kind) = SecretsProviderKind::from_str(kind_value.trim()) else {
+            report.push(ConfigError::UnknownProvider {
+                provider: provider_name.to_owned(),
+                name: kind_var,
+                kind: kind_value,
+                supported: "vault, file, mounted".to_owned(),
+            });
+            continue;
+        };
+
+        match kind.load_provider(&uppercase_name).await {
+            Ok(provider) => read.push(provider),
+            Err(err) => {
+                report.push(match err.downcast::<ConfigError>() {
+                    Ok(config_error) => config_error,
+                    Err(err) => ConfigError::ProviderLoadFailed {
+                        provider: provider_name.to_owned(),
+                        reason: format!("{:#}", err),
+                    },
+                });
+            }
         }
     }
 
-    read
+    report.into_result(read)
+}
+
+fn get_default_base_path() -> String {
+    get_environment().get_secrets_base_path()
 }
 
-fn load_vault_secrets_provider(provider_name: String) -> Vec<SecretsProvider> {
-    let mut vault_providers: Vec<SecretsProvider> = Vec::new();
+fn load_provider_from_env<ProviderType: for<'a> Deserialize<'a> + DataProvisionActions>(
+    provider_name: &str,
+) -> Result<ProviderType, ConfigError> {
+    let prefix = format!("{}_", provider_name);
+
+    envy::prefixed(&prefix)
+        .from_env::<ProviderType>()
+        .map_err(|err| ConfigError::from_envy(&prefix, err))
+}
 
-    let parsed_env_data: VaultEnvData = load_provider_from_env::<VaultEnvData>(&provider_name);
+// Files ending in .toml are TOML, everything else has to be JSON
+fn read_structured_file(path: &Path) -> Result<serde_json::Value> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read secrets file {}", path.display()))?;
 
-    let connection_info = URLData {
-        host: parsed_env_data.get_host().to_string(),
-        port: parsed_env_data.get_port(),
-        url: parsed_env_data.get_url().to_string(),
-    };
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => toml::from_str(&contents)
+            .with_context(|| format!("Secrets file {} is not valid TOML", path.display())),
+        _ => serde_json::from_str(&contents)
+            .with_context(|| format!("Secrets file {} is not valid JSON", path.display())),
+    }
+}
 
-    let provision_type = parsed_env_data.get_provision_type();
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
-    let implementation = Some(SecretsProviderImplementation::Vault(Vault::new(
-        parsed_env_data,
-        VaultSecretsEngine::KV2,
-    )));
+    use async_trait::async_trait;
 
-    vault_providers.push(DataProvider {
-        name: provider_name.to_string().to_lowercase(),
-        prefix: format!("{}_", provider_name.to_string().to_lowercase()),
-        connection_info,
-        provision_type,
-        connectivity: DataProviderConnectivity::SingleConnection,
-        implementation,
-    });
+    use super::*;
 
-    vault_providers
-}
+    struct LeasingBackend {
+        reads: AtomicUsize,
+    }
 
-fn load_provider_from_env<ProviderType: for<'a> Deserialize<'a> + DataProvisionActions>(
-    provider_name: &str,
-) -> ProviderType {
-    return match envy::prefixed(format!("{}_", provider_name)).from_env::<ProviderType>() {
-            Ok(sec_prov) => sec_prov,
-            Err(_) => panic!("Encountered error during loading of Secrets Provider, the name \"{}\" might be misspelled or related variables are missing", provider_name),
-        };
+    #[async_trait]
+    impl SecretsBackend for LeasingBackend {
+        fn get_kind(&self) -> SecretsProviderKind {
+            SecretsProviderKind::Vault
+        }
+
+        async fn read_secret(&self, _path: &str) -> Result<serde_json::Value> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            Ok(serde_json::json!({ "pg_user": "v-celestus-1", "pg_password": "pw" }))
+        }
+
+        async fn list_keys(&self, _path: &str) -> Result<Vec<String>> {
+            Ok(Vec::new())
+        }
+
+        fn issues_leases(&self) -> bool {
+            true
+        }
+
+        async fn health(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn leased_credentials_are_not_read_through_the_cache() {
+        let backend = Arc::new(LeasingBackend {
+            reads: AtomicUsize::new(0),
+        });
+        let mut providers = SecretsProviders::start(&SecretsOptions {
+            providers: vec!["vault".to_owned()],
+            cache_ttl: Duration::from_secs(300),
+            watch_interval: Duration::from_secs(3600),
+        });
+        providers.register("vault", DataProvision::OneTime, backend.clone());
+
+        let read = providers
+            .get::<serde_json::Value>("vault", POSTGRES_SECRETS_PATH)
+            .await;
+
+        assert!(matches!(read, Err(SecretsError::Unsupported(_))));
+        assert_eq!(backend.reads.load(Ordering::SeqCst), 0);
+    }
 }
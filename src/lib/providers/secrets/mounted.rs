@@ -0,0 +1,297 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use getset::Getters;
+use serde_derive::Deserialize;
+
+use crate::providers::{
+    DataProvider, DataProviderConnectivity, DataProvision, DataProvisionActions,
+};
+
+use super::backend::{SecretsBackend, SecretsProviderKind};
+use super::errors::SecretsError;
+use super::{load_provider_from_env, read_structured_file, SecretsProvider};
+
+pub const DEFAULT_MOUNTED_SECRETS_PATH: &str = "/run/secrets";
+
+#[derive(Deserialize, Getters)]
+#[getset(get = "pub with_prefix")]
+pub struct MountedEnvData {
+    path: Option<String>,
+    base_path: Option<String>,
+    single_use: Option<bool>,
+}
+
+impl DataProvisionActions for MountedEnvData {
+    fn get_provision_type(&self) -> DataProvision {
+        DataProvision::from_single_use(self.single_use)
+    }
+}
+
+#[derive(Getters)]
+#[getset(get = "pub with_prefix")]
+pub struct MountedSecrets {
+    root: PathBuf,
+    base_path: String,
+}
+
+impl MountedSecrets {
+    pub fn new(provider_info: &MountedEnvData) -> Result<Self> {
+        let root = PathBuf::from(
+            provider_info
+                .path
+                .as_deref()
+                .unwrap_or(DEFAULT_MOUNTED_SECRETS_PATH),
+        );
+        if !root.is_dir() {
+            return Err(anyhow!(
+                "Mounted secrets directory {} does not exist",
+                root.display()
+            ));
+        }
+
+        Ok(Self {
+            root,
+            base_path: provider_info.base_path.clone().unwrap_or_default(),
+        })
+    }
+
+    fn candidates(&self, path: &str) -> Vec<PathBuf> {
+        let full_path = format!("{}{}", self.base_path, path)
+            .trim_matches('/')
+            .to_string();
+        let flat_name = full_path.replace('/', "_");
+
+        vec![
+            self.root.join(&flat_name),
+            self.root.join(format!("{}.json", flat_name)),
+            self.root.join(format!("{}.toml", flat_name)),
+            self.root.join(&full_path),
+        ]
+    }
+}
+
+#[async_trait]
+impl SecretsBackend for MountedSecrets {
+    fn get_kind(&self) -> SecretsProviderKind {
+        SecretsProviderKind::Mounted
+    }
+
+    async fn read_secret(&self, path: &str) -> Result<serde_json::Value> {
+        for candidate in self.candidates(path) {
+            if candidate.is_dir() {
+                return read_directory_as_object(&candidate);
+            }
+            if candidate.is_file() {
+                return read_structured_file(&candidate);
+            }
+        }
+
+        Err(SecretsError::NotFound(format!(
+            "\"{}\" is not mounted in {}",
+            path,
+            self.root.display()
+        ))
+        .into())
+    }
+
+    async fn list_keys(&self, path: &str) -> Result<Vec<String>> {
+        let directory = self
+            .candidates(path)
+            .into_iter()
+            .find(|candidate| candidate.is_dir())
+            .ok_or_else(|| anyhow!("\"{}\" is not a folder of mounted secrets", path))?;
+
+        let mut keys = Vec::new();
+        for entry in fs::read_dir(directory)? {
+            if let Some(name) = entry?.file_name().to_str() {
+                keys.push(name.to_string());
+            }
+        }
+        keys.sort();
+
+        Ok(keys)
+    }
+
+    async fn health(&self) -> Result<()> {
+        if self.root.is_dir() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Mounted secrets directory {} is gone",
+                self.root.display()
+            ))
+        }
+    }
+}
+
+pub(super) fn load_mounted_secrets_provider(provider_name: &str) -> Result<SecretsProvider> {
+    let parsed_env_data: MountedEnvData = load_provider_from_env::<MountedEnvData>(provider_name)?;
+
+    let provision_type = parsed_env_data.get_provision_type();
+
+    let implementation = MountedSecrets::new(&parsed_env_data)?;
+
+    Ok(DataProvider {
+        name: provider_name.to_lowercase(),
+        prefix: format!("{}_", provider_name.to_lowercase()),
+        connection_info: None,
+        provision_type,
+        connectivity: DataProviderConnectivity::SingleConnection,
+        implementation: Some(Arc::new(implementation)),
+    })
+}
+
+fn read_directory_as_object(path: &Path) -> Result<serde_json::Value> {
+    let mut object = serde_json::Map::new();
+
+    for entry in fs::read_dir(path)? {
+        let entry_path = entry?.path();
+        if !entry_path.is_file() {
+            continue;
+        }
+        let Some(name) = entry_path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if name.starts_with('.') {
+            continue;
+        }
+
+        let contents = fs::read_to_string(&entry_path)
+            .with_context(|| format!("Failed to read mounted secret {}", entry_path.display()))?;
+        object.insert(
+            name.to_string(),
+            serde_json::Value::String(contents.trim_end_matches(['\r', '\n']).to_string()),
+        );
+    }
+
+    Ok(serde_json::Value::Object(object))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn mounted_secrets(root: &Path) -> MountedSecrets {
+        MountedSecrets::new(&MountedEnvData {
+            path: Some(root.display().to_string()),
+            base_path: Some("celestus/".to_string()),
+            single_use: None,
+        })
+        .unwrap()
+    }
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[tokio::test]
+    async fn candidates_are_tried_in_order() {
+        let root = TempDir::new().unwrap();
+        let secrets = mounted_secrets(root.path());
+
+        write(
+            root.path(),
+            "celestus/database/pg/username",
+            "from-nested-directory",
+        );
+        assert_eq!(
+            secrets.read_secret("database/pg").await.unwrap(),
+            json!({"username": "from-nested-directory"})
+        );
+
+        write(
+            root.path(),
+            "celestus_database_pg/username",
+            "from-field-directory",
+        );
+        assert_eq!(
+            secrets.read_secret("database/pg").await.unwrap(),
+            json!({"username": "from-field-directory"})
+        );
+        fs::remove_dir_all(root.path().join("celestus_database_pg")).unwrap();
+
+        write(
+            root.path(),
+            "celestus_database_pg.toml",
+            "username = \"from-toml\"\n",
+        );
+        assert_eq!(
+            secrets.read_secret("database/pg").await.unwrap(),
+            json!({"username": "from-toml"})
+        );
+
+        write(
+            root.path(),
+            "celestus_database_pg.json",
+            r#"{"username": "from-json"}"#,
+        );
+        assert_eq!(
+            secrets.read_secret("database/pg").await.unwrap(),
+            json!({"username": "from-json"})
+        );
+
+        write(
+            root.path(),
+            "celestus_database_pg",
+            r#"{"username": "from-flat-file"}"#,
+        );
+        assert_eq!(
+            secrets.read_secret("database/pg").await.unwrap(),
+            json!({"username": "from-flat-file"})
+        );
+    }
+
+    #[tokio::test]
+    async fn field_directories_trim_newlines_and_skip_dotfiles() {
+        let root = TempDir::new().unwrap();
+        write(root.path(), "celestus_database_pg/username", "celestus\n");
+        write(
+            root.path(),
+            "celestus_database_pg/password",
+            "se cret \r\n\n",
+        );
+        write(root.path(), "celestus_database_pg/..data", "kubelet link");
+        write(root.path(), "celestus_database_pg/.hidden", "ignored");
+        write(root.path(), "celestus_database_pg/nested/field", "ignored");
+        let secrets = mounted_secrets(root.path());
+
+        assert_eq!(
+            secrets.read_secret("database/pg").await.unwrap(),
+            json!({"username": "celestus", "password": "se cret "})
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_secrets_are_not_found() {
+        let root = TempDir::new().unwrap();
+        let secrets = mounted_secrets(root.path());
+
+        let err = secrets.read_secret("database/pg").await.unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<SecretsError>(),
+            Some(SecretsError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn missing_root_is_rejected() {
+        let root = TempDir::new().unwrap();
+
+        assert!(MountedSecrets::new(&MountedEnvData {
+            path: Some(root.path().join("missing").display().to_string()),
+            base_path: None,
+            single_use: None,
+        })
+        .is_err());
+    }
+}
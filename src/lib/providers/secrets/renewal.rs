@@ -0,0 +1,436 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use log::{error, info, warn};
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+use crate::providers::DataProviderName;
+use crate::utils::runtime::get_handle;
+
+use super::watch::SecretsNotifier;
+use super::{SecretsProvider, SecretsProviderImplementation};
+
+const RENEWAL_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+const MINIMUM_LEASE_RENEWAL_INTERVAL: Duration = Duration::from_secs(1);
+
+type LeaseKey = (DataProviderName, String);
+
+#[derive(Clone, Debug)]
+pub struct SecretLease {
+    pub lease_id: String,
+    pub duration: Duration,
+    pub renewable: bool,
+}
+
+struct KeptLease {
+    lease_id: String,
+    backend: SecretsProviderImplementation,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl KeptLease {
+    fn abort_worker(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            worker.abort();
+        }
+    }
+}
+
+pub struct SecretsRenewal {
+    workers: Vec<JoinHandle<()>>,
+    leases: Mutex<HashMap<LeaseKey, KeptLease>>,
+}
+
+impl SecretsRenewal {
+    pub fn start(providers: &HashMap<DataProviderName, SecretsProvider>) -> Self {
+        let mut created = Self {
+            workers: Vec::new(),
+            leases: Mutex::new(HashMap::new()),
+        };
+        created.restart(providers);
+
+        created
+    }
+
+    pub fn is_renewing(&self) -> bool {
+        !self.workers.is_empty()
+    }
+
+    // Kept leases outlive a restart, they belong to credentials already handed out
+    pub fn restart(&mut self, providers: &HashMap<DataProviderName, SecretsProvider>) {
+        self.abort_workers();
+
+        // Any provision type can hold an expiring token or lease, only the backend knows
+        for provider in providers.values() {
+            let Some(backend) = provider.get_implementation() else {
+                continue;
+            };
+            if backend.get_renewal_interval().is_none() {
+                continue;
+            }
+
+            info!(
+                "Starting credentials renewal for Secrets Provider {}",
+                provider.get_name()
+            );
+            self.workers
+                .push(spawn_worker(provider.get_name().clone(), backend.clone()));
+        }
+    }
+
+    // The lease being replaced is left to expire, connections still using it can finish
+    pub fn keep_lease(
+        &self,
+        provider_name: DataProviderName,
+        path: &str,
+        backend: SecretsProviderImplementation,
+        lease: SecretLease,
+        notifier: Option<SecretsNotifier>,
+    ) {
+        let worker = match lease.duration.is_zero() {
+            true => None,
+            false => Some(spawn_lease_worker(
+                provider_name.clone(),
+                path.to_owned(),
+                backend.clone(),
+                lease.clone(),
+                notifier,
+            )),
+        };
+        let kept = KeptLease {
+            lease_id: lease.lease_id,
+            backend,
+            worker,
+        };
+
+        let Ok(mut leases) = self.leases.lock() else {
+            return;
+        };
+        if let Some(mut replaced) = leases.insert((provider_name, path.to_owned()), kept) {
+            replaced.abort_worker();
+        }
+    }
+
+    pub async fn revoke_leases(&self) {
+        let kept: Vec<KeptLease> = match self.leases.lock() {
+            Ok(mut leases) => leases.drain().map(|(_, kept)| kept).collect(),
+            Err(_) => return,
+        };
+
+        for mut lease in kept {
+            lease.abort_worker();
+            match lease.backend.revoke_lease(&lease.lease_id).await {
+                Ok(_) => info!("Revoked lease {}", lease.lease_id),
+                Err(err) => warn!("Failed to revoke lease {}: {}", lease.lease_id, err),
+            }
+        }
+    }
+
+    pub fn stop(mut self) {
+        self.abort_workers();
+        self.abort_lease_workers();
+    }
+
+    fn abort_workers(&mut self) {
+        for worker in self.workers.drain(..) {
+            worker.abort();
+        }
+    }
+
+    fn abort_lease_workers(&self) {
+        if let Ok(mut leases) = self.leases.lock() {
+            leases.values_mut().for_each(KeptLease::abort_worker);
+        }
+    }
+}
+
+impl Drop for SecretsRenewal {
+    fn drop(&mut self) {
+        self.abort_workers();
+        self.abort_lease_workers();
+    }
+}
+
+fn spawn_worker(provider_name: String, backend: SecretsProviderImplementation) -> JoinHandle<()> {
+    get_handle().spawn(async move {
+        loop {
+            let wait = backend
+                .get_renewal_interval()
+                .unwrap_or(RENEWAL_RETRY_INTERVAL);
+            sleep(wait).await;
+
+            match backend.renew().await {
+                Ok(_) => info!("Renewed credentials of Secrets Provider {}", provider_name),
+                Err(err) => {
+                    error!(
+                        "Failed to renew credentials of Secrets Provider {}: {}",
+                        provider_name, err
+                    );
+                    sleep(RENEWAL_RETRY_INTERVAL).await;
+                }
+            }
+        }
+    })
+}
+
+// Once the lease cannot be extended by its full duration the subscribers are told to fetch new
+// credentials, a third of the lease is left for them to do it
+fn spawn_lease_worker(
+    provider_name: DataProviderName,
+    path: String,
+    backend: SecretsProviderImplementation,
+    lease: SecretLease,
+    notifier: Option<SecretsNotifier>,
+) -> JoinHandle<()> {
+    get_handle().spawn(async move {
+        let mut duration = lease.duration;
+        loop {
+            sleep((duration * 2 / 3).max(MINIMUM_LEASE_RENEWAL_INTERVAL)).await;
+            if !lease.renewable {
+                break;
+            }
+
+            match backend.renew_lease(&lease.lease_id, lease.duration).await {
+                Ok(granted) if granted >= lease.duration => {
+                    info!(
+                        "Renewed lease {} for {}s",
+                        lease.lease_id,
+                        granted.as_secs()
+                    );
+                    duration = granted;
+                }
+                Ok(granted) => {
+                    warn!(
+                        "Lease {} reached its maximum TTL, {}s left",
+                        lease.lease_id,
+                        granted.as_secs()
+                    );
+                    break;
+                }
+                Err(err) => {
+                    error!("Failed to renew lease {}: {}", lease.lease_id, err);
+                    break;
+                }
+            }
+        }
+
+        info!(
+            "Lease {} of \"{}\" is running out, requesting new credentials",
+            lease.lease_id, path
+        );
+        if let Some(notifier) = notifier {
+            notifier
+                .notify(&provider_name, &path, &lease.lease_id)
+                .await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use anyhow::Result;
+    use async_trait::async_trait;
+
+    use super::*;
+    use crossbeam::channel::unbounded;
+
+    use crate::providers::secrets::backend::{SecretsBackend, SecretsProviderKind};
+    use crate::providers::secrets::cache::SecretsCache;
+    use crate::providers::secrets::watch::SecretsWatcher;
+    use crate::providers::{DataProvider, DataProviderConnectivity, DataProvision};
+
+    struct LeasedBackend {
+        renewal_interval: Option<Duration>,
+        lease_granted: Duration,
+        renewals: AtomicUsize,
+        lease_renewals: AtomicUsize,
+        lease_revocations: AtomicUsize,
+    }
+
+    impl LeasedBackend {
+        fn new(renewal_interval: Option<Duration>, lease_granted: Duration) -> Arc<Self> {
+            Arc::new(Self {
+                renewal_interval,
+                lease_granted,
+                renewals: AtomicUsize::new(0),
+                lease_renewals: AtomicUsize::new(0),
+                lease_revocations: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl SecretsBackend for LeasedBackend {
+        fn get_kind(&self) -> SecretsProviderKind {
+            SecretsProviderKind::File
+        }
+
+        async fn read_secret(&self, _path: &str) -> Result<serde_json::Value> {
+            Ok(serde_json::Value::Null)
+        }
+
+        async fn list_keys(&self, _path: &str) -> Result<Vec<String>> {
+            Ok(Vec::new())
+        }
+
+        async fn renew(&self) -> Result<()> {
+            self.renewals.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn get_renewal_interval(&self) -> Option<Duration> {
+            self.renewal_interval
+        }
+
+        fn issues_leases(&self) -> bool {
+            true
+        }
+
+        async fn renew_lease(&self, _lease_id: &str, _increment: Duration) -> Result<Duration> {
+            self.lease_renewals.fetch_add(1, Ordering::SeqCst);
+            Ok(self.lease_granted)
+        }
+
+        async fn revoke_lease(&self, _lease_id: &str) -> Result<()> {
+            self.lease_revocations.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn health(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn providers_with(
+        provision_type: DataProvision,
+        backend: Arc<LeasedBackend>,
+    ) -> HashMap<DataProviderName, SecretsProvider> {
+        let mut providers = HashMap::new();
+        providers.insert(
+            "leased".into(),
+            DataProvider {
+                name: "leased".to_owned(),
+                prefix: "leased_".to_owned(),
+                connection_info: None,
+                provision_type,
+                connectivity: DataProviderConnectivity::SingleConnection,
+                implementation: Some(backend as SecretsProviderImplementation),
+            },
+        );
+
+        providers
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn renews_expiring_credentials_of_any_provision_type() {
+        for provision_type in [DataProvision::OneTime, DataProvision::OnDemand] {
+            let backend = LeasedBackend::new(Some(Duration::from_millis(10)), Duration::ZERO);
+
+            let renewal = SecretsRenewal::start(&providers_with(provision_type, backend.clone()));
+            assert!(renewal.is_renewing());
+            sleep(Duration::from_millis(100)).await;
+            renewal.stop();
+
+            assert!(backend.renewals.load(Ordering::SeqCst) > 0);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn skips_backends_without_expiring_credentials() {
+        let backend = LeasedBackend::new(None, Duration::ZERO);
+
+        assert!(
+            !SecretsRenewal::start(&providers_with(DataProvision::OnDemand, backend)).is_renewing()
+        );
+    }
+
+    fn lease(duration: Duration, renewable: bool) -> SecretLease {
+        SecretLease {
+            lease_id: "database/creds/pg/1".to_owned(),
+            duration,
+            renewable,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn keeps_leases_until_they_are_revoked() {
+        // Leases are renewed no more than once a second
+        let lease_duration = Duration::from_millis(1500);
+        let backend = LeasedBackend::new(None, lease_duration);
+        let renewal = SecretsRenewal::start(&HashMap::new());
+
+        renewal.keep_lease(
+            "leased".into(),
+            "database/pg",
+            backend.clone(),
+            lease(lease_duration, true),
+            None,
+        );
+        sleep(Duration::from_millis(1200)).await;
+        renewal.revoke_leases().await;
+        let renewals = backend.lease_renewals.load(Ordering::SeqCst);
+        sleep(Duration::from_millis(1200)).await;
+
+        assert!(renewals > 0);
+        assert_eq!(backend.lease_renewals.load(Ordering::SeqCst), renewals);
+        assert_eq!(backend.lease_revocations.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn keeps_renewing_leases_across_restarts() {
+        let lease_duration = Duration::from_millis(1500);
+        let backend = LeasedBackend::new(None, lease_duration);
+        let mut renewal = SecretsRenewal::start(&HashMap::new());
+
+        renewal.keep_lease(
+            "leased".into(),
+            "database/pg",
+            backend.clone(),
+            lease(lease_duration, true),
+            None,
+        );
+        renewal.restart(&providers_with(
+            DataProvision::OneTime,
+            LeasedBackend::new(None, Duration::ZERO),
+        ));
+        sleep(Duration::from_millis(2500)).await;
+        renewal.stop();
+
+        assert!(backend.lease_renewals.load(Ordering::SeqCst) >= 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn asks_for_new_credentials_before_a_lease_runs_out() {
+        let backend = LeasedBackend::new(None, Duration::from_millis(5));
+        let watcher = SecretsWatcher::start(
+            Arc::new(SecretsCache::new(Duration::ZERO)),
+            Duration::from_secs(3600),
+        );
+        let (sender, receiver) = unbounded();
+        watcher
+            .subscribe("leased".into(), "database/pg", backend.clone(), sender)
+            .await
+            .unwrap();
+        let renewal = SecretsRenewal::start(&HashMap::new());
+
+        for renewable in [true, false] {
+            renewal.keep_lease(
+                "leased".into(),
+                "database/pg",
+                backend.clone(),
+                lease(Duration::from_millis(1500), renewable),
+                Some(watcher.get_notifier()),
+            );
+
+            let change = receiver.recv_timeout(Duration::from_secs(3)).unwrap();
+            assert_eq!(change.path, "database/pg");
+            assert_eq!(change.version, "database/creds/pg/1");
+        }
+        assert_eq!(backend.lease_renewals.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,45 +1,80 @@
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use base64::prelude::{Engine as _, BASE64_STANDARD};
 use getset::Getters;
-use log::info;
-use serde_derive::Deserialize;
-use tokio::runtime::Runtime;
-use vaultrs::client::{VaultClient, VaultClientSettingsBuilder};
-use vaultrs::kv2;
+use log::{error, warn};
+use rustify::endpoint::Endpoint;
+use rustify::enums::{RequestMethod, RequestType, ResponseType};
+use rustify::errors::ClientError as RestClientError;
+use rustify::http::build_body;
+use serde_derive::{Deserialize, Serialize};
+use tokio::sync::{RwLock, RwLockReadGuard};
+use vaultrs::api::database::requests::GenerateCredentialsRequest;
+use vaultrs::api::database::responses::GenerateCredentialsResponse;
+use vaultrs::api::{exec_with_empty, exec_with_no_result, AuthInfo, EndpointResult};
+use vaultrs::client::{Client, VaultClient, VaultClientSettingsBuilder};
+use vaultrs::error::ClientError;
+use vaultrs::{auth, database, kv1, kv2, sys, token, transit};
 use vaultrs_login::engines::approle::AppRoleLogin;
-use vaultrs_login::LoginClient;
 
-use crate::providers::data::business::postgres::PostgresData;
-use crate::providers::{DataProviderConnectivity, DataProvision, DataProvisionActions};
-use crate::utils::environment::{is_dev_mode, is_docker_host};
-use crate::utils::web::URLInfo;
+use crate::providers::{
+    DataProvider, DataProviderConnectivity, DataProvision, DataProvisionActions,
+};
+use crate::utils::environment::get_environment;
+use crate::utils::errors::ConfigError;
+use crate::utils::web::{URLData, URLInfo};
+
+use super::backend::{fingerprint, SecretsBackend, SecretsProviderKind};
+use super::errors::SecretsError;
+use super::{get_default_base_path, load_provider_from_env, SecretsProvider};
+
+const APPROLE_SECRET_ID_PATH_PREFIX: &str = "auth/approle/role/";
+const APPROLE_SECRET_ID_PATH_SUFFIX: &str = "/secret-id";
 
-pub const VAULT_SECRETS_PROVIDER_NAME: &str = "VAULT";
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(10);
+const MINIMUM_RENEWAL_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Deserialize, Getters)]
 #[getset(get = "pub with_prefix")]
 pub struct VaultEnvData {
     #[getset(skip)]
-    host: String,
+    host: Option<String>,
     #[getset(skip)]
-    port: i32,
+    port: Option<i32>,
     #[getset(skip)]
+    #[serde(default)]
     url: String,
+    address: Option<String>,
     engine: String,
+    mount: Option<String>,
+    namespace: Option<String>,
+    base_path: Option<String>,
+    ca_cert: Option<String>,
+    skip_verify: Option<bool>,
     login_id: String,
-    login_pass: String,
+    login_pass: Option<String>,
+    login_wrapped_token: Option<String>,
     single_use: Option<bool>,
 }
 
 impl DataProvisionActions for VaultEnvData {
     fn get_provision_type(&self) -> DataProvision {
-        match self.get_single_use() {
-            Some(bool) => {
-                if *bool {
-                    DataProvision::OneTime
-                } else {
-                    DataProvision::OnDemand
-                }
-            }
-            None => DataProvision::OneTime,
+        DataProvision::from_single_use(self.single_use)
+    }
+}
+
+impl VaultEnvData {
+    // ADDRESS wins over URL, HOST and PORT are only used when neither is set
+    fn resolve_address(&self) -> Option<String> {
+        match (&self.address, self.url.is_empty(), &self.host, self.port) {
+            (Some(address), _, _, _) => Some(address.clone()),
+            (None, false, _, _) => Some(self.url.clone()),
+            (None, true, Some(host), Some(port)) => Some(format!("http://{}:{}", host, port)),
+            _ => None,
         }
     }
 }
@@ -50,11 +85,11 @@ impl URLInfo for VaultEnvData {
     }
 
     fn get_host(&self) -> &str {
-        self.host.as_str()
+        self.host.as_deref().unwrap_or_default()
     }
 
     fn get_port(&self) -> i32 {
-        self.port
+        self.port.unwrap_or_default()
     }
 }
 
@@ -66,85 +101,615 @@ struct VaultWrappedSecret {
     secret_id_ttl: i32,
 }
 
+struct TokenLease {
+    issued_at: Instant,
+    ttl: Duration,
+    renewable: bool,
+}
+
+impl TokenLease {
+    fn from_auth_info(auth_info: &AuthInfo) -> Self {
+        Self {
+            issued_at: Instant::now(),
+            ttl: Duration::from_secs(auth_info.lease_duration),
+            renewable: auth_info.renewable,
+        }
+    }
+
+    fn expires(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+
+    fn get_remaining(&self) -> Duration {
+        self.ttl.saturating_sub(self.issued_at.elapsed())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, strum_macros::EnumString, strum_macros::Display)]
+#[strum(ascii_case_insensitive)]
 pub enum VaultSecretsEngine {
+    KV1,
     KV2,
+    Database,
+    Transit,
+}
+
+impl VaultSecretsEngine {
+    pub fn get_default_mount(&self) -> &'static str {
+        match self {
+            VaultSecretsEngine::KV1 | VaultSecretsEngine::KV2 => "kv",
+            VaultSecretsEngine::Database => "database",
+            VaultSecretsEngine::Transit => "transit",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct VaultDatabaseCredentials {
+    pub username: String,
+    pub password: String,
+    pub lease_id: String,
+    pub lease_duration: u32,
+    pub renewable: bool,
+}
+
+// vaultrs has no lease endpoints
+#[derive(Debug, Serialize)]
+struct RenewLeaseRequest {
+    lease_id: String,
+    increment: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct RenewLeaseResponse {
+    lease_duration: u64,
+}
+
+impl Endpoint for RenewLeaseRequest {
+    type Response = RenewLeaseResponse;
+    const REQUEST_BODY_TYPE: RequestType = RequestType::JSON;
+    const RESPONSE_BODY_TYPE: ResponseType = ResponseType::JSON;
+
+    fn path(&self) -> String {
+        "sys/leases/renew".to_owned()
+    }
+
+    fn method(&self) -> RequestMethod {
+        RequestMethod::PUT
+    }
+
+    fn body(&self) -> Result<Option<Vec<u8>>, RestClientError> {
+        build_body(self, Self::REQUEST_BODY_TYPE).map(Some)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RevokeLeaseRequest {
+    lease_id: String,
+}
+
+impl Endpoint for RevokeLeaseRequest {
+    type Response = ();
+    const REQUEST_BODY_TYPE: RequestType = RequestType::JSON;
+    const RESPONSE_BODY_TYPE: ResponseType = ResponseType::JSON;
+
+    fn path(&self) -> String {
+        "sys/leases/revoke".to_owned()
+    }
+
+    fn method(&self) -> RequestMethod {
+        RequestMethod::PUT
+    }
+
+    fn body(&self) -> Result<Option<Vec<u8>>, RestClientError> {
+        build_body(self, Self::REQUEST_BODY_TYPE).map(Some)
+    }
 }
 
 #[derive(Getters)]
 #[getset(get = "pub with_prefix")]
 pub struct Vault {
-    client: VaultClient,
+    client: RwLock<VaultClient>,
     connectivity: DataProviderConnectivity,
     secrets_engine: VaultSecretsEngine,
+    mount: String,
     base_path: String,
-    runtime: Runtime,
+    #[getset(skip)]
+    login: AppRoleLogin,
+    #[getset(skip)]
+    token_lease: Mutex<Option<TokenLease>>,
 }
 
 impl Vault {
-    pub fn new(provider_info: VaultEnvData, secrets_engine: VaultSecretsEngine) -> Self {
-        let runtime = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .unwrap();
+    pub async fn new(
+        provider_info: VaultEnvData,
+        secrets_engine: VaultSecretsEngine,
+    ) -> Result<Self> {
+        let mut client = create_vault_client(&provider_info)?;
 
-        let client = create_vault_client(&provider_info);
+        let login = create_approle_login(&mut client, &provider_info).await?;
 
-        let base_path = match is_dev_mode() {
-            true => "dev/celestus/".to_owned(),
-            false => "celestus/".to_owned(),
-        };
+        let mount = provider_info
+            .mount
+            .clone()
+            .unwrap_or_else(|| secrets_engine.get_default_mount().to_owned());
+
+        let base_path = provider_info
+            .base_path
+            .clone()
+            .unwrap_or_else(get_default_base_path);
 
-        let mut created = Self {
-            client,
+        let created = Self {
+            client: RwLock::new(client),
+            mount,
             secrets_engine,
             connectivity: DataProviderConnectivity::SingleConnection,
             base_path,
-            runtime,
+            login,
+            token_lease: Mutex::new(None),
         };
 
-        created.login_with_approle(created.create_approle_login(&provider_info));
+        created.login_with_approle().await?;
+
+        Ok(created)
+    }
+
+    async fn login_with_approle(&self) -> Result<()> {
+        let client = self.read_client().await;
+        let auth_info = auth::approle::login(
+            &*client,
+            "approle",
+            &self.login.role_id,
+            &self.login.secret_id,
+        )
+        .await?;
+        drop(client);
+
+        self.client.write().await.set_token(&auth_info.client_token);
+        self.store_lease(&auth_info)?;
+
+        Ok(())
+    }
+
+    async fn renew_token(&self) -> Result<()> {
+        let client = self.read_client().await;
+        let auth_info = token::renew_self(&*client, None).await?;
+        drop(client);
+
+        self.store_lease(&auth_info)
+    }
 
-        let asd = created.get_kv_data::<PostgresData>("database/pg");
-        info!("{:#?}", asd);
+    fn store_lease(&self, auth_info: &AuthInfo) -> Result<()> {
+        *self
+            .token_lease
+            .lock()
+            .map_err(|_| anyhow!("Vault token lease lock is poisoned"))? =
+            Some(TokenLease::from_auth_info(auth_info));
 
-        created
+        Ok(())
     }
 
-    fn create_approle_login(&self, provider_info: &VaultEnvData) -> AppRoleLogin {
-        AppRoleLogin {
-            role_id: provider_info.login_id.clone(),
-            secret_id: provider_info.login_pass.clone(),
+    async fn ensure_token(&self) -> Result<()> {
+        let expiring = match self
+            .token_lease
+            .lock()
+            .map_err(|_| anyhow!("Vault token lease lock is poisoned"))?
+            .as_ref()
+        {
+            Some(lease) => lease.expires() && lease.get_remaining() <= TOKEN_EXPIRY_MARGIN,
+            None => true,
+        };
+
+        if expiring {
+            self.renew().await?;
         }
+
+        Ok(())
     }
 
-    fn login_with_approle(&mut self, login: AppRoleLogin) {
-        self.runtime
-            .block_on(self.client.login("approle", &login))
-            .unwrap()
+    async fn read_client(&self) -> RwLockReadGuard<'_, VaultClient> {
+        self.client.read().await
     }
 
-    fn get_kv_data<DataStruct: for<'de> serde::Deserialize<'de>>(&self, path: &str) -> DataStruct {
-        self.runtime
-            .block_on(kv2::read::<DataStruct>(
-                &self.client,
-                "kv",
-                &format!("{}{}", self.base_path, path),
+    fn full_path(&self, path: &str) -> String {
+        format!("{}{}", self.base_path, path)
+    }
+
+    async fn get_kv_data<DataStruct: for<'de> serde::Deserialize<'de>>(
+        &self,
+        path: &str,
+    ) -> Result<DataStruct> {
+        self.ensure_token().await?;
+        let client = self.read_client().await;
+        let full_path = self.full_path(path);
+
+        let data = match self.secrets_engine {
+            VaultSecretsEngine::KV1 => {
+                kv1::get::<DataStruct>(&*client, &self.mount, &full_path).await?
+            }
+            VaultSecretsEngine::KV2 => {
+                kv2::read::<DataStruct>(&*client, &self.mount, &full_path).await?
+            }
+            VaultSecretsEngine::Database => {
+                let credentials = self.get_database_credentials(&client, path).await?;
+                serde_json::from_value(serde_json::to_value(credentials)?)?
+            }
+            VaultSecretsEngine::Transit => {
+                return Err(SecretsError::Unsupported(
+                    "Vault transit engine does not store secrets, use encrypt/decrypt instead"
+                        .to_owned(),
+                )
+                .into())
+            }
+        };
+
+        Ok(data)
+    }
+
+    async fn get_database_credentials(
+        &self,
+        client: &VaultClient,
+        path: &str,
+    ) -> Result<VaultDatabaseCredentials> {
+        let role = get_database_role_name(path);
+        let endpoint = GenerateCredentialsRequest::builder()
+            .mount(&self.mount)
+            .name(&role)
+            .build()?;
+
+        let response = endpoint
+            .with_middleware(client.middle())
+            .exec(client.http())
+            .await
+            .map_err(map_rest_client_error)?
+            .wrap::<EndpointResult<GenerateCredentialsResponse>>()
+            .map_err(ClientError::from)?;
+
+        let credentials = response.data.ok_or(ClientError::ResponseDataEmptyError)?;
+
+        Ok(VaultDatabaseCredentials {
+            username: credentials.username,
+            password: credentials.password,
+            lease_id: response.lease_id,
+            lease_duration: response.lease_duration,
+            renewable: response.renewable,
+        })
+    }
+
+    fn require_transit(&self) -> Result<()> {
+        match self.secrets_engine {
+            VaultSecretsEngine::Transit => Ok(()),
+            engine => Err(SecretsError::Unsupported(format!(
+                "Vault provider is configured with the {} engine, not Transit",
+                engine
             ))
-            .unwrap()
+            .into()),
+        }
     }
 }
 
-fn create_vault_client(provider_info: &VaultEnvData) -> VaultClient {
-    let address: String = if is_dev_mode() && is_docker_host() {
-        "http://vault-dev:8201".to_owned()
-    } else {
-        provider_info.url.clone()
+#[async_trait]
+impl SecretsBackend for Vault {
+    fn get_kind(&self) -> SecretsProviderKind {
+        SecretsProviderKind::Vault
+    }
+
+    async fn read_secret(&self, path: &str) -> Result<serde_json::Value> {
+        self.get_kv_data::<serde_json::Value>(path).await
+    }
+
+    async fn list_keys(&self, path: &str) -> Result<Vec<String>> {
+        self.ensure_token().await?;
+        let client = self.read_client().await;
+        let full_path = self.full_path(path);
+
+        let keys = match self.secrets_engine {
+            VaultSecretsEngine::KV1 => {
+                kv1::list(&*client, &self.mount, &full_path)
+                    .await?
+                    .data
+                    .keys
+            }
+            VaultSecretsEngine::KV2 => kv2::list(&*client, &self.mount, &full_path).await?,
+            VaultSecretsEngine::Database => database::role::list(&*client, &self.mount).await?.keys,
+            VaultSecretsEngine::Transit => transit::key::list(&*client, &self.mount).await?.keys,
+        };
+
+        Ok(keys)
+    }
+
+    async fn get_version(&self, path: &str) -> Result<Option<String>> {
+        match self.secrets_engine {
+            VaultSecretsEngine::KV1 => Ok(Some(fingerprint(&self.read_secret(path).await?))),
+            VaultSecretsEngine::KV2 => {
+                self.ensure_token().await?;
+                let client = self.read_client().await;
+                let metadata =
+                    kv2::read_metadata(&*client, &self.mount, &self.full_path(path)).await?;
+
+                Ok(Some(metadata.current_version.to_string()))
+            }
+            VaultSecretsEngine::Database | VaultSecretsEngine::Transit => Ok(None),
+        }
+    }
+
+    async fn encrypt(&self, key: &str, plaintext: &[u8]) -> Result<String> {
+        self.require_transit()?;
+        self.ensure_token().await?;
+        let client = self.read_client().await;
+
+        let response = transit::data::encrypt(
+            &*client,
+            &self.mount,
+            key,
+            &BASE64_STANDARD.encode(plaintext),
+            None,
+        )
+        .await?;
+
+        Ok(response.ciphertext)
+    }
+
+    async fn decrypt(&self, key: &str, ciphertext: &str) -> Result<Vec<u8>> {
+        self.require_transit()?;
+        self.ensure_token().await?;
+        let client = self.read_client().await;
+
+        let response = transit::data::decrypt(&*client, &self.mount, key, ciphertext, None).await?;
+
+        Ok(BASE64_STANDARD.decode(response.plaintext)?)
+    }
+
+    async fn login(&self) -> Result<()> {
+        self.login_with_approle().await
+    }
+
+    async fn renew(&self) -> Result<()> {
+        let renewable = self
+            .token_lease
+            .lock()
+            .map_err(|_| anyhow!("Vault token lease lock is poisoned"))?
+            .as_ref()
+            .is_some_and(|lease| lease.renewable && !lease.get_remaining().is_zero());
+
+        if renewable {
+            match self.renew_token().await {
+                Ok(_) => return Ok(()),
+                Err(err) => warn!("Vault token renewal failed, logging in again: {}", err),
+            }
+        }
+
+        self.login_with_approle().await
+    }
+
+    fn get_renewal_interval(&self) -> Option<Duration> {
+        let lease = self.token_lease.lock().ok()?;
+        let lease = lease.as_ref()?;
+        if !lease.expires() {
+            return None;
+        }
+
+        Some((lease.get_remaining() * 2 / 3).max(MINIMUM_RENEWAL_INTERVAL))
+    }
+
+    fn issues_leases(&self) -> bool {
+        self.secrets_engine == VaultSecretsEngine::Database
+    }
+
+    async fn renew_lease(&self, lease_id: &str, increment: Duration) -> Result<Duration> {
+        self.ensure_token().await?;
+        let client = self.read_client().await;
+        let endpoint = RenewLeaseRequest {
+            lease_id: lease_id.to_owned(),
+            increment: increment.as_secs(),
+        };
+
+        let response = exec_with_no_result(&*client, endpoint).await?;
+
+        Ok(Duration::from_secs(response.lease_duration))
+    }
+
+    async fn revoke_lease(&self, lease_id: &str) -> Result<()> {
+        self.ensure_token().await?;
+        let client = self.read_client().await;
+        let endpoint = RevokeLeaseRequest {
+            lease_id: lease_id.to_owned(),
+        };
+
+        Ok(exec_with_empty(&*client, endpoint).await?)
+    }
+
+    async fn health(&self) -> Result<()> {
+        let client = self.read_client().await;
+        sys::health(&*client).await?;
+
+        Ok(())
+    }
+}
+
+pub(super) async fn load_vault_secrets_provider(provider_name: &str) -> Result<SecretsProvider> {
+    let parsed_env_data: VaultEnvData = load_provider_from_env::<VaultEnvData>(provider_name)?;
+
+    let Some(address) = parsed_env_data.resolve_address() else {
+        return Err(ConfigError::InvalidVariables {
+            prefix: format!("{}_", provider_name),
+            reason: String::from("none of ADDRESS, URL or HOST and PORT is set"),
+        }
+        .into());
     };
 
-    let client_settings = VaultClientSettingsBuilder::default()
+    let connection_info = URLData {
+        host: parsed_env_data.get_host().to_string(),
+        port: parsed_env_data.get_port(),
+        url: address,
+    };
+
+    let provision_type = parsed_env_data.get_provision_type();
+
+    let secrets_engine = VaultSecretsEngine::from_str(parsed_env_data.get_engine().trim())
+        .map_err(|_| {
+            anyhow!(
+                "Unknown Vault engine \"{}\", supported: KV1, KV2, Database, Transit",
+                parsed_env_data.get_engine()
+            )
+        })?;
+
+    let implementation = Vault::new(parsed_env_data, secrets_engine).await?;
+
+    Ok(DataProvider {
+        name: provider_name.to_lowercase(),
+        prefix: format!("{}_", provider_name.to_lowercase()),
+        connection_info: Some(connection_info),
+        provision_type,
+        connectivity: DataProviderConnectivity::SingleConnection,
+        implementation: Some(Arc::new(implementation)),
+    })
+}
+
+fn get_database_role_name(path: &str) -> String {
+    path.trim_matches('/').replace('/', "-")
+}
+
+fn map_rest_client_error(err: RestClientError) -> ClientError {
+    match err {
+        RestClientError::ServerResponseError { code, content } => ClientError::APIError {
+            code,
+            errors: content.into_iter().collect(),
+        },
+        err => ClientError::from(err),
+    }
+}
+
+async fn create_approle_login(
+    client: &mut VaultClient,
+    provider_info: &VaultEnvData,
+) -> Result<AppRoleLogin> {
+    let secret_id = match (
+        &provider_info.login_wrapped_token,
+        &provider_info.login_pass,
+    ) {
+        (Some(wrapped_token), _) => unwrap_secret_id(client, wrapped_token).await?,
+        (None, Some(login_pass)) => login_pass.clone(),
+        (None, None) => {
+            return Err(anyhow!(
+                "Vault needs either LOGIN_PASS or LOGIN_WRAPPED_TOKEN to log in with AppRole"
+            ))
+        }
+    };
+
+    Ok(AppRoleLogin {
+        role_id: provider_info.login_id.clone(),
+        secret_id,
+    })
+}
+
+async fn unwrap_secret_id(client: &mut VaultClient, wrapped_token: &str) -> Result<String> {
+    let lookup = match sys::wrapping::lookup(&*client, wrapped_token).await {
+        Ok(lookup) => lookup,
+        Err(err) => {
+            error!(
+                "Vault wrapping token is invalid, expired or was already unwrapped! \
+                The AppRole secret_id might have been intercepted: {}",
+                err
+            );
+            return Err(anyhow!("Vault wrapping token lookup failed"));
+        }
+    };
+
+    if !lookup
+        .creation_path
+        .starts_with(APPROLE_SECRET_ID_PATH_PREFIX)
+        || !lookup
+            .creation_path
+            .ends_with(APPROLE_SECRET_ID_PATH_SUFFIX)
+    {
+        error!(
+            "Vault wrapping token was created by \"{}\" instead of an AppRole secret_id request!",
+            lookup.creation_path
+        );
+        return Err(anyhow!("Vault wrapping token has an unexpected origin"));
+    }
+
+    client.set_token(wrapped_token);
+    let unwrapped = sys::wrapping::unwrap::<VaultWrappedSecret>(&*client, None).await;
+    client.set_token("");
+
+    match unwrapped {
+        Ok(wrapped_secret) => Ok(wrapped_secret.secret_id),
+        Err(err) => {
+            error!(
+                "Failed to unwrap the AppRole secret_id! \
+                The wrapping token might have been used by someone else: {}",
+                err
+            );
+            Err(anyhow!("Vault wrapping token unwrap failed"))
+        }
+    }
+}
+
+fn create_vault_client(provider_info: &VaultEnvData) -> Result<VaultClient> {
+    let address = provider_info
+        .resolve_address()
+        .ok_or_else(|| anyhow!("Vault address is not configured"))?;
+
+    let mut builder = VaultClientSettingsBuilder::default();
+    builder
         .address(address)
-        .build()
-        .unwrap();
+        .namespace(provider_info.namespace.clone());
+
+    if let Some(ca_cert) = &provider_info.ca_cert {
+        builder.ca_certs(vec![ca_cert.clone()]);
+    }
 
-    VaultClient::new(client_settings).unwrap()
+    if provider_info.skip_verify == Some(true) {
+        let environment = get_environment();
+        if !environment.is_local() {
+            return Err(anyhow!(
+                "Vault TLS verification cannot be skipped in the {} environment",
+                environment
+            ));
+        }
+        warn!("Vault TLS certificate verification is disabled");
+        builder.verify(false);
+    }
+
+    let client_settings = builder.build()?;
+
+    Ok(VaultClient::new(client_settings)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_data(variables: &[(&str, &str)]) -> VaultEnvData {
+        let mut variables: Vec<(String, String)> = variables
+            .iter()
+            .map(|(name, value)| (format!("VAULT_{}", name), value.to_string()))
+            .collect();
+        variables.push(("VAULT_ENGINE".to_owned(), "kv2".to_owned()));
+        variables.push(("VAULT_LOGIN_ID".to_owned(), "celestus".to_owned()));
+
+        envy::prefixed("VAULT_").from_iter(variables).unwrap()
+    }
+
+    #[test]
+    fn address_is_taken_from_address_url_or_host_and_port() {
+        let address = "https://vault.internal:8443";
+
+        assert_eq!(
+            env_data(&[("ADDRESS", address), ("URL", "http://ignored:8200")]).resolve_address(),
+            Some(address.to_owned())
+        );
+        assert_eq!(
+            env_data(&[("URL", address)]).resolve_address(),
+            Some(address.to_owned())
+        );
+        assert_eq!(
+            env_data(&[("HOST", "localhost"), ("PORT", "8200")]).resolve_address(),
+            Some("http://localhost:8200".to_owned())
+        );
+        assert_eq!(env_data(&[("HOST", "localhost")]).resolve_address(), None);
+        assert_eq!(env_data(&[]).resolve_address(), None);
+    }
 }
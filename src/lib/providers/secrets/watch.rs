@@ -0,0 +1,194 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossbeam::channel::Sender;
+use log::{info, warn};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+use crate::providers::DataProviderName;
+use crate::utils::runtime::get_handle;
+
+use super::cache::SecretsCache;
+use super::SecretsProviderImplementation;
+
+pub const ENV_SECRETS_WATCH_INTERVAL: &str = "SECRETS_WATCH_INTERVAL";
+
+#[derive(Clone, Debug)]
+pub struct SecretChange {
+    pub provider_name: DataProviderName,
+    pub path: String,
+    pub version: String,
+}
+
+struct Subscription {
+    provider_name: DataProviderName,
+    path: String,
+    backend: SecretsProviderImplementation,
+    version: Option<String>,
+    sender: Sender<SecretChange>,
+}
+
+type Subscriptions = Arc<Mutex<Vec<Subscription>>>;
+
+pub struct SecretsWatcher {
+    subscriptions: Subscriptions,
+    cache: Arc<SecretsCache>,
+    worker: Option<JoinHandle<()>>,
+}
+
+// Reports changes the watcher cannot poll for, like leased credentials running out
+#[derive(Clone)]
+pub struct SecretsNotifier {
+    subscriptions: Subscriptions,
+    cache: Arc<SecretsCache>,
+}
+
+impl SecretsNotifier {
+    pub async fn notify(&self, provider_name: &DataProviderName, path: &str, version: &str) {
+        self.cache.invalidate(provider_name, path);
+
+        let mut subscriptions = self.subscriptions.lock().await;
+        subscriptions.retain(|subscription| {
+            if subscription.provider_name != *provider_name || subscription.path != path {
+                return true;
+            }
+
+            subscription
+                .sender
+                .send(SecretChange {
+                    provider_name: provider_name.clone(),
+                    path: path.to_owned(),
+                    version: version.to_owned(),
+                })
+                .is_ok()
+        });
+    }
+}
+
+impl SecretsWatcher {
+    pub fn start(cache: Arc<SecretsCache>, interval: Duration) -> Self {
+        let subscriptions: Subscriptions = Arc::new(Mutex::new(Vec::new()));
+
+        let worker = spawn_worker(interval, subscriptions.clone(), cache.clone());
+
+        Self {
+            subscriptions,
+            cache,
+            worker: Some(worker),
+        }
+    }
+
+    pub fn get_notifier(&self) -> SecretsNotifier {
+        SecretsNotifier {
+            subscriptions: self.subscriptions.clone(),
+            cache: self.cache.clone(),
+        }
+    }
+
+    pub async fn subscribe(
+        &self,
+        provider_name: DataProviderName,
+        path: &str,
+        backend: SecretsProviderImplementation,
+        sender: Sender<SecretChange>,
+    ) -> anyhow::Result<()> {
+        // Leased secrets have no versions, their changes come through the notifier
+        let version = match backend.issues_leases() {
+            true => None,
+            false => backend.get_version(path).await?,
+        };
+        if version.is_none() && !backend.issues_leases() {
+            return Err(anyhow::anyhow!(
+                "Secrets Provider cannot report versions of \"{}\"",
+                path
+            ));
+        }
+
+        self.subscriptions.lock().await.push(Subscription {
+            provider_name,
+            path: path.to_owned(),
+            backend,
+            version,
+            sender,
+        });
+
+        Ok(())
+    }
+
+    pub fn stop(mut self) {
+        self.abort_worker();
+    }
+
+    fn abort_worker(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            worker.abort();
+        }
+    }
+}
+
+impl Drop for SecretsWatcher {
+    fn drop(&mut self) {
+        self.abort_worker();
+    }
+}
+
+fn spawn_worker(
+    interval: Duration,
+    subscriptions: Subscriptions,
+    cache: Arc<SecretsCache>,
+) -> JoinHandle<()> {
+    get_handle().spawn(async move {
+        loop {
+            sleep(interval).await;
+            poll(&subscriptions, &cache).await;
+        }
+    })
+}
+
+async fn poll(subscriptions: &Subscriptions, cache: &SecretsCache) {
+    let mut subscriptions = subscriptions.lock().await;
+    let mut active = Vec::with_capacity(subscriptions.len());
+
+    for subscription in subscriptions.iter_mut() {
+        active.push(check(subscription, cache).await);
+    }
+
+    let mut active = active.into_iter();
+    subscriptions.retain(|_| active.next().unwrap_or(true));
+}
+
+async fn check(subscription: &mut Subscription, cache: &SecretsCache) -> bool {
+    let version = match subscription.backend.get_version(&subscription.path).await {
+        Ok(Some(version)) => version,
+        Ok(None) => return true,
+        Err(err) => {
+            warn!(
+                "Failed to check \"{}\" of Secrets Provider {}: {}",
+                subscription.path, subscription.provider_name.0, err
+            );
+            return true;
+        }
+    };
+
+    if subscription.version.as_deref() == Some(version.as_str()) {
+        return true;
+    }
+
+    info!(
+        "Secret \"{}\" of Secrets Provider {} changed to version {}",
+        subscription.path, subscription.provider_name.0, version
+    );
+    cache.invalidate(&subscription.provider_name, &subscription.path);
+    subscription.version = Some(version.clone());
+
+    subscription
+        .sender
+        .send(SecretChange {
+            provider_name: subscription.provider_name.clone(),
+            path: subscription.path.clone(),
+            version,
+        })
+        .is_ok()
+}
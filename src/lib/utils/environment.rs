@@ -1,155 +1,376 @@
-use std::{env, fmt::Display, str::FromStr};
+use std::{env, fmt::Display, fs, path::Path, str::FromStr, sync::OnceLock};
 
-use anyhow::Error;
+use anyhow::{anyhow, Error};
 use log::{info, warn};
 
-pub const SETTING_HOST_MODE: &str = "host_mode";
+use super::errors::ConfigError;
 
-pub const SETTING_HOST_ENVIRONMENT: &str = "host_environment";
+pub const ENV_FILE_SUFFIX: &str = "_FILE";
+
+pub const ENV_HOST_TYPE: &str = "HOST_TYPE";
+
+pub const SETTING_HOST_ENVIRONMENT: &str = "host.environment";
 pub const ENV_HOST_ENVIRONMENT: &str = "HOST_ENVIRONMENT";
+pub const ENVIRONMENT_NAMES: &[&str] = &[
+    "development",
+    "dev",
+    "test",
+    "staging",
+    "production",
+    "prod",
+];
+
+const ENV_FILE_PATH: &str = ".env";
+
+const ENV_KUBERNETES_SERVICE_HOST: &str = "KUBERNETES_SERVICE_HOST";
+const DOCKER_MARKER_PATH: &str = "/.dockerenv";
+const PODMAN_MARKER_PATH: &str = "/run/.containerenv";
+const PROC_CGROUP_PATH: &str = "/proc/self/cgroup";
+const PROC_MOUNTINFO_PATH: &str = "/proc/self/mountinfo";
+const CGROUP_MARKERS: [(&str, HostType); 5] = [
+    ("kubepods", HostType::Kubernetes),
+    ("libpod", HostType::Podman),
+    ("/docker/", HostType::Docker),
+    ("docker-", HostType::Docker),
+    ("/containerd/", HostType::Docker),
+];
+
+static ENVIRONMENT: OnceLock<Environment> = OnceLock::new();
+static HOST_TYPE: OnceLock<HostType> = OnceLock::new();
 
-#[derive(PartialEq, strum_macros::EnumString, strum_macros::Display)]
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, strum_macros::EnumString, strum_macros::Display,
+)]
+#[strum(ascii_case_insensitive)]
 pub enum Environment {
-    Production,
+    #[strum(to_string = "development", serialize = "dev")]
     Development,
-}
-
-#[derive(strum_macros::EnumString, strum_macros::Display)]
-pub enum HostMode {
+    #[strum(to_string = "test")]
+    Test,
+    #[strum(to_string = "staging")]
+    Staging,
+    #[default]
+    #[strum(to_string = "production", serialize = "prod")]
     Production,
-    Development,
 }
 
-pub fn is_dev_mode() -> bool {
-    if cfg!(debug_assertions) {
-        return true;
+impl Environment {
+    pub fn get_short_name(&self) -> &'static str {
+        match self {
+            Environment::Development => "dev",
+            Environment::Test => "test",
+            Environment::Staging => "staging",
+            Environment::Production => "prod",
+        }
+    }
+
+    pub fn get_secrets_base_path(&self) -> String {
+        match self {
+            Environment::Production => "celestus/".to_owned(),
+            _ => format!("{}/celestus/", self.get_short_name()),
+        }
+    }
+
+    pub fn get_default_log_filter(&self) -> &'static str {
+        match self {
+            Environment::Development => "info,celestus=debug",
+            Environment::Test => "warn",
+            Environment::Staging | Environment::Production => "info",
+        }
     }
-    false
-}
 
-pub fn is_prod_mode() -> bool {
-    !is_dev_mode()
+    // Only local environments may load .env files or weaken security checks
+    pub fn is_local(&self) -> bool {
+        matches!(self, Environment::Development | Environment::Test)
+    }
 }
 
-pub fn get_host_mode() -> HostMode {
-    if is_dev_mode() {
-        return HostMode::Development;
+pub fn get_environment() -> Environment {
+    match ENVIRONMENT.get() {
+        Some(environment) => *environment,
+        None => read_environment().unwrap_or_default(),
     }
-    HostMode::Production
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum_macros::EnumString, strum_macros::Display)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
 pub enum HostType {
-    Local,
-    Container,
+    BareMetal,
+    Docker,
+    Podman,
+    Kubernetes,
 }
 
-pub fn is_docker_host() -> bool {
-    let process_id_1 = procfs::process::all_processes()
-        .expect("Can't read /proc")
-        .find(|proc| {
-            let stats = proc.as_ref().unwrap().stat().unwrap();
-            stats.pid == 1 && !stats.comm.contains("sh")
-        });
-
-    if let Some(proc) = process_id_1 {
-        let process = proc.unwrap();
-        let stats = process.stat().unwrap();
-        info!("Running locally. {} {}", stats.pid, stats.comm);
-        false
-    } else {
-        info!("Running in a container.");
-        true
+impl HostType {
+    pub fn is_container(&self) -> bool {
+        *self != HostType::BareMetal
     }
 }
 
+pub fn get_host_type() -> HostType {
+    *HOST_TYPE.get_or_init(detect_host_type)
+}
+
+pub fn is_container_host() -> bool {
+    get_host_type().is_container()
+}
+
 pub fn is_local_host() -> bool {
-    !is_docker_host()
+    !is_container_host()
 }
 
-pub fn get_host_type() -> HostType {
-    if is_docker_host() {
-        return HostType::Container;
+fn detect_host_type() -> HostType {
+    if let Ok(Some(host_type)) = read_host_type_override() {
+        return host_type;
     }
-    HostType::Local
-}
 
-pub fn init_environment() {
-    if is_dev_mode() {
-        info!("Running in development mode.");
-        init_dev_environment();
-    } else if is_prod_mode() {
-        info!("Running in production mode.");
-        init_prod_environment();
+    if env::var_os(ENV_KUBERNETES_SERVICE_HOST).is_some() {
+        return HostType::Kubernetes;
+    }
+    if Path::new(PODMAN_MARKER_PATH).exists() {
+        return HostType::Podman;
+    }
+    if Path::new(DOCKER_MARKER_PATH).exists() {
+        return HostType::Docker;
     }
+
+    let cgroup = fs::read_to_string(PROC_CGROUP_PATH).unwrap_or_default();
+    let mountinfo = fs::read_to_string(PROC_MOUNTINFO_PATH).unwrap_or_default();
+
+    detect_container_runtime(&cgroup, &mountinfo).unwrap_or(HostType::BareMetal)
 }
 
-pub fn init_dev_environment() {
-    if is_local_host() {
-        info!("Loading .env file");
-        dotenvy::from_path(".env").expect("No .env file found!");
-    } else {
-        info!("Will not look for .env file")
-    }
+// cgroup v1 names the runtime in the cgroup paths, cgroup v2 only shows it in the root mount.
+// Other mounts are left alone, a host running containers lists their storage there too
+fn detect_container_runtime(cgroup: &str, mountinfo: &str) -> Option<HostType> {
+    let cgroup_paths = cgroup.lines().filter_map(|line| line.splitn(3, ':').nth(2));
+    let root_mount = mountinfo
+        .lines()
+        .rev()
+        .find(|line| line.split_whitespace().nth(4) == Some("/"));
+
+    cgroup_paths.chain(root_mount).find_map(|contents| {
+        CGROUP_MARKERS
+            .iter()
+            .find(|(marker, _)| contents.contains(marker))
+            .map(|(_, host_type)| *host_type)
+    })
+}
 
-    let env = env::var("HOST_ENVIRONMENT")
-        .expect("Unknown environment! Environment variable HOST_ENVIRONMENT must be set!");
-    match env.to_lowercase().as_str() {
-        "dev" => Environment::Development,
-        "development" => Environment::Development,
-        "prod" => Environment::Production,
-        "production" => Environment::Production,
-        val => panic!(
-            "Unknown value \"{}\" for environment variable HOST_ENVIRONMENT!",
-            val
-        ),
+fn read_host_type_override() -> Result<Option<HostType>, ConfigError> {
+    let name = match env::var(ENV_HOST_TYPE) {
+        Ok(name) if !name.trim().is_empty() => name,
+        _ => return Ok(None),
     };
+
+    name.trim()
+        .parse::<HostType>()
+        .map(Some)
+        .map_err(|_| ConfigError::InvalidValue {
+            name: ENV_HOST_TYPE.to_owned(),
+            value: name,
+            reason: String::from("expected one of bare_metal, docker, podman, kubernetes"),
+        })
+}
+
+pub fn init_environment() -> Result<Environment, ConfigError> {
+    let env_file = load_env_file();
+    let environment = read_environment();
+    init_logger(environment.as_ref().copied().unwrap_or_default());
+
+    match env_file? {
+        true => info!("Loaded {} file", ENV_FILE_PATH),
+        false => info!("Will not look for {} file", ENV_FILE_PATH),
+    }
+
+    read_host_type_override()?;
+    info!("Host type is {}", get_host_type());
+
+    let environment = environment?;
+    if ENVIRONMENT.set(environment).is_err() && get_environment() != environment {
+        warn!(
+            "Environment is already initialized as {}, ignoring {}",
+            get_environment(),
+            environment
+        );
+    }
     info!(
-        "Runnign like {} environment (determined by HOST_ENVIRONMENT)",
-        env
+        "Running as {} environment (determined by {})",
+        get_environment(),
+        ENV_HOST_ENVIRONMENT
     );
+
+    Ok(get_environment())
+}
+
+fn init_logger(environment: Environment) {
+    let _ = env_logger::Builder::from_env(
+        env_logger::Env::default().default_filter_or(environment.get_default_log_filter()),
+    )
+    .try_init();
 }
 
-pub fn init_prod_environment() {
-    if is_local_host() {
-        info!("Will not look for .env file");
-    } else {
-        info!("Will not look for .env file");
+// The .env file is never read when the process environment already asks for staging or production.
+// is_none_or needs Rust 1.82, the Docker image builds with 1.75
+#[allow(clippy::unnecessary_map_or)]
+fn load_env_file() -> Result<bool, ConfigError> {
+    let requested = env::var(ENV_HOST_ENVIRONMENT).ok();
+    let local = requested
+        .and_then(|name| name.trim().parse::<Environment>().ok())
+        .map_or(true, |environment| environment.is_local());
+    if !local || !Path::new(ENV_FILE_PATH).exists() || is_container_host() {
+        return Ok(false);
     }
+
+    dotenvy::from_path(ENV_FILE_PATH)
+        .map(|_| true)
+        .map_err(|err| ConfigError::EnvFileFailed {
+            path: ENV_FILE_PATH.to_owned(),
+            reason: err.to_string(),
+        })
+}
+
+fn read_environment() -> Result<Environment, ConfigError> {
+    let Ok(name) = env::var(ENV_HOST_ENVIRONMENT) else {
+        return Ok(Environment::default());
+    };
+
+    name.trim()
+        .parse::<Environment>()
+        .map_err(|_| ConfigError::UnknownEnvironment {
+            name: ENV_HOST_ENVIRONMENT.to_owned(),
+            value: name,
+        })
 }
 
-pub fn get_env_var<VarType>(name: &str, default: Option<VarType>) -> Result<VarType, Error>
+pub fn get_env_var<VarType>(name: &str, default: Option<VarType>) -> Result<VarType, ConfigError>
 where
     VarType: FromStr + Display,
-    <VarType as FromStr>::Err: std::fmt::Debug,
+    <VarType as FromStr>::Err: Display,
 {
-    let result: VarType;
-    if let Some(value) = default {
-        result = env::var(name)
-            .unwrap_or_else(|_| {
-                warn!(
-                    "Environment variable {} is not defined. Defaulting to {}",
-                    name, value
-                );
-                value.to_string()
-            })
-            .parse::<VarType>()
-            .unwrap_or(value);
-        Ok(result)
-    } else {
-        let result = env::var(name)
-            .unwrap_or_else(|_| {
-                panic!(
-                    "Environment variable {} is not defined, and a default value is not assigned",
-                    name
-                )
+    let raw = match (env::var(name), default) {
+        (Ok(raw), _) => raw,
+        (Err(_), Some(value)) => {
+            warn!(
+                "Environment variable {} is not defined. Defaulting to {}",
+                name, value
+            );
+            return Ok(value);
+        }
+        (Err(_), None) => {
+            return Err(ConfigError::MissingVariable {
+                name: name.to_owned(),
             })
-            .parse()
-            .unwrap_or_else(|_| {
-                panic!(
-                    "Environment variable {} is defined but it's type cannot be read.",
-                    name
-                )
-            });
-        Ok(result)
+        }
+    };
+
+    raw.trim()
+        .parse::<VarType>()
+        .map_err(|err| ConfigError::InvalidValue {
+            name: name.to_owned(),
+            value: raw.clone(),
+            reason: err.to_string(),
+        })
+}
+
+pub fn get_env_var_or_file(name: &str) -> Result<String, Error> {
+    if let Ok(value) = env::var(name) {
+        return Ok(value);
+    }
+
+    let file_var = format!("{}{}", name, ENV_FILE_SUFFIX);
+    match env::var(&file_var) {
+        Ok(path) => match fs::read_to_string(path.trim()) {
+            Ok(contents) => Ok(contents.trim_end_matches(['\r', '\n']).to_string()),
+            Err(err) => Err(anyhow!(
+                "Environment variable {} points to {}, which cannot be read: {}",
+                file_var,
+                path,
+                err
+            )),
+        },
+        Err(_) => Err(anyhow!(
+            "Environment variable {} (or {}) is not defined",
+            name,
+            file_var
+        )),
+    }
+}
+
+// Tests reading the process environment take turns with the ones changing it
+#[cfg(test)]
+pub(crate) fn lock_test_env() -> std::sync::MutexGuard<'static, ()> {
+    static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    ENV_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST_CGROUP_V1: &str = "12:pids:/user.slice/user-1000.slice/session-2.scope
+4:memory:/user.slice/user-1000.slice/session-2.scope
+1:name=systemd:/user.slice/user-1000.slice/session-2.scope
+";
+    const HOST_CGROUP_V2: &str = "0::/user.slice/user-1000.slice/session-2.scope\n";
+    const HOST_MOUNTINFO: &str = "22 1 259:2 / / rw,relatime shared:1 - ext4 /dev/nvme0n1p2 rw
+25 22 0:22 / /proc rw,nosuid,nodev,noexec,relatime shared:12 - proc proc rw
+";
+    // A host running Docker and containerd sees their storage and namespaces in its own mounts
+    const HOST_WITH_DOCKER_MOUNTINFO: &str = "22 1 259:2 / / rw,relatime shared:1 - ext4 /dev/nvme0n1p2 rw
+481 22 0:52 / /var/lib/docker/overlay2/5c1f/merged rw,relatime - overlay overlay rw,lowerdir=/var/lib/docker/overlay2/l/QX2L,upperdir=/var/lib/docker/overlay2/5c1f/diff
+502 29 0:4 net:[4026532601] /run/docker/netns/8a2d9c rw shared:245 - nsfs nsfs rw
+517 22 0:53 / /run/containerd/io.containerd.runtime.v2.task/moby/5c1f/rootfs rw,relatime - overlay overlay rw
+";
+    const DOCKER_CGROUP_V1: &str = "12:pids:/docker/5c1f0e3a
+1:name=systemd:/docker/5c1f0e3a
+";
+    const CONTAINER_CGROUP_V2: &str = "0::/\n";
+    const DOCKER_MOUNTINFO: &str = "1321 1189 0:118 / / rw,relatime master:364 - overlay overlay rw,lowerdir=/var/lib/docker/overlay2/l/QX2L,upperdir=/var/lib/docker/overlay2/5c1f/diff
+1322 1321 0:121 / /proc rw,nosuid,nodev,noexec,relatime - proc proc rw
+";
+    const CONTAINERD_MOUNTINFO: &str = "2164 2009 0:302 / / rw,relatime - overlay overlay rw,lowerdir=/var/lib/containerd/io.containerd.snapshotter.v1.overlayfs/snapshots/41/fs
+";
+    const KUBERNETES_CGROUP_V1: &str = "11:memory:/kubepods/burstable/pod6f1e/5c1f0e3a\n";
+    const PODMAN_CGROUP_V1: &str = "1:name=systemd:/machine.slice/libpod-5c1f0e3a.scope\n";
+
+    #[test]
+    fn bare_metal_hosts_are_not_containers() {
+        for cgroup in [HOST_CGROUP_V1, HOST_CGROUP_V2, ""] {
+            for mountinfo in [HOST_MOUNTINFO, HOST_WITH_DOCKER_MOUNTINFO, ""] {
+                assert_eq!(detect_container_runtime(cgroup, mountinfo), None);
+            }
+        }
+    }
+
+    #[test]
+    fn cgroup_v1_paths_name_the_runtime() {
+        assert_eq!(
+            detect_container_runtime(DOCKER_CGROUP_V1, HOST_MOUNTINFO),
+            Some(HostType::Docker)
+        );
+        assert_eq!(
+            detect_container_runtime(KUBERNETES_CGROUP_V1, ""),
+            Some(HostType::Kubernetes)
+        );
+        assert_eq!(
+            detect_container_runtime(PODMAN_CGROUP_V1, ""),
+            Some(HostType::Podman)
+        );
+    }
+
+    #[test]
+    fn cgroup_v2_containers_are_found_by_their_root_mount() {
+        assert_eq!(
+            detect_container_runtime(CONTAINER_CGROUP_V2, DOCKER_MOUNTINFO),
+            Some(HostType::Docker)
+        );
+        assert_eq!(
+            detect_container_runtime(CONTAINER_CGROUP_V2, CONTAINERD_MOUNTINFO),
+            Some(HostType::Docker)
+        );
     }
 }
@@ -0,0 +1,132 @@
+use std::fmt;
+
+use ::thiserror::Error;
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Environment variable {name} is not defined!")]
+    MissingVariable { name: String },
+    #[error("Environment variable {name}=\"{value}\" cannot be read: {reason}")]
+    InvalidValue {
+        name: String,
+        value: String,
+        reason: String,
+    },
+    #[error("Setting {key}=\"{value}\" from {origin} cannot be read: {reason}")]
+    InvalidSetting {
+        key: String,
+        origin: String,
+        value: String,
+        reason: String,
+    },
+    #[error("Environment variables {prefix}* cannot be read: {reason}")]
+    InvalidVariables { prefix: String, reason: String },
+    #[error("Unknown environment \"{value}\" in {name}!")]
+    UnknownEnvironment { name: String, value: String },
+    #[error("Secrets Provider \"{provider}\" has unknown kind \"{kind}\" in {name}, supported: {supported}")]
+    UnknownProvider {
+        provider: String,
+        name: String,
+        kind: String,
+        supported: String,
+    },
+    #[error("Secrets Provider \"{provider}\" failed to load: {reason}")]
+    ProviderLoadFailed { provider: String, reason: String },
+    #[error("Settings from {origin} failed to load: {reason}")]
+    SourceLoadFailed { origin: String, reason: String },
+    #[error("Failed to load {path}: {reason}")]
+    EnvFileFailed { path: String, reason: String },
+    #[error("Config file {path} cannot be read: {reason}")]
+    ConfigFileFailed { path: String, reason: String },
+}
+
+impl ConfigError {
+    pub fn from_envy(prefix: &str, err: envy::Error) -> Self {
+        match err {
+            envy::Error::MissingValue(field) => ConfigError::MissingVariable {
+                name: format!("{}{}", prefix, field.to_uppercase()),
+            },
+            envy::Error::Custom(reason) => ConfigError::InvalidVariables {
+                prefix: prefix.to_owned(),
+                reason,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ConfigReport {
+    errors: Vec<ConfigError>,
+}
+
+impl ConfigReport {
+    pub fn push(&mut self, error: ConfigError) {
+        self.errors.push(error);
+    }
+
+    pub fn extend(&mut self, other: ConfigReport) {
+        self.errors.extend(other.errors);
+    }
+
+    pub fn take<Value>(&mut self, result: Result<Value, ConfigError>) -> Option<Value> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.push(err);
+                None
+            }
+        }
+    }
+
+    pub fn take_all<Value>(&mut self, result: Result<Value, ConfigReport>) -> Option<Value> {
+        match result {
+            Ok(value) => Some(value),
+            Err(report) => {
+                self.extend(report);
+                None
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn get_errors(&self) -> &[ConfigError] {
+        &self.errors
+    }
+
+    pub fn into_result<Value>(self, value: Value) -> Result<Value, ConfigReport> {
+        if self.is_empty() {
+            Ok(value)
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl From<ConfigError> for ConfigReport {
+    fn from(error: ConfigError) -> Self {
+        Self {
+            errors: vec![error],
+        }
+    }
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Found {} configuration problem(s), fix them and start again:",
+            self.errors.len()
+        )?;
+        for error in self.errors.iter() {
+            writeln!(f, "  - {}", error)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigReport {}
@@ -1,3 +1,5 @@
 pub mod environment;
+pub mod errors;
+pub mod runtime;
 pub mod strings;
 pub mod web;
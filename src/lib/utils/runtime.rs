@@ -0,0 +1,40 @@
+use std::future::Future;
+use std::sync::OnceLock;
+use std::thread;
+
+use tokio::runtime::{Builder, Handle, Runtime, RuntimeFlavor};
+
+static SHARED_RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+fn get_shared_runtime() -> &'static Runtime {
+    SHARED_RUNTIME.get_or_init(|| {
+        Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("celestus-shared")
+            .build()
+            .expect("Failed to start the shared Tokio runtime!")
+    })
+}
+
+pub fn get_handle() -> Handle {
+    Handle::try_current().unwrap_or_else(|_| get_shared_runtime().handle().clone())
+}
+
+pub fn block_on<F>(future: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| handle.block_on(future))
+        }
+        Ok(_) => thread::scope(|scope| {
+            scope
+                .spawn(|| get_shared_runtime().block_on(future))
+                .join()
+                .expect("Blocking task panicked!")
+        }),
+        Err(_) => get_shared_runtime().block_on(future),
+    }
+}